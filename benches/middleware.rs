@@ -0,0 +1,78 @@
+//! Request latency with many middleware scopes registered.
+//!
+//! Run with `cargo bench --features testing --bench middleware`.
+
+use std::time::{Duration, Instant};
+
+use bolt_web::{
+    App,
+    async_trait::async_trait,
+    request::RequestBody,
+    response::ResponseWriter,
+    testing::TestClient,
+    types::{Middleware, MiddlewareOutcome},
+};
+
+const SCOPES: usize = 500;
+const WARMUP: usize = 500;
+const ITERATIONS: usize = 5_000;
+
+struct Noop;
+
+#[async_trait]
+impl Middleware for Noop {
+    async fn handle(&self, _req: &mut RequestBody, _res: &mut ResponseWriter) -> MiddlewareOutcome {
+        MiddlewareOutcome::Continue
+    }
+}
+
+async fn ok(_: &mut RequestBody, res: &mut ResponseWriter) {
+    res.text("ok");
+}
+
+fn app() -> App {
+    let mut app = App::new();
+    app.set_banner(false);
+
+    for i in 0..SCOPES {
+        app.middleware(&format!("/svc/{}", i), None, Noop);
+        app.get(&format!("/svc/{}/items", i), ok);
+    }
+
+    for scope in ["/", "/api", "/api/v1", "/api/v1/users", "/api/v1/users/:id"] {
+        app.middleware(scope, None, Noop);
+    }
+    app.get("/api/v1/users/:id/posts", ok);
+
+    app
+}
+
+async fn measure(client: &TestClient, path: &str) -> Duration {
+    for _ in 0..WARMUP {
+        client.get(path).await.unwrap();
+    }
+
+    let started = Instant::now();
+    for _ in 0..ITERATIONS {
+        let res = client.get(path).await.unwrap();
+        assert_eq!(res.status, 200);
+    }
+    started.elapsed() / ITERATIONS as u32
+}
+
+#[tokio::main]
+async fn main() {
+    let client = TestClient::start(app()).await.unwrap();
+
+    let cases = [
+        ("nested", "/api/v1/users/7/posts".to_string()),
+        ("sibling", format!("/svc/{}/items", SCOPES - 1)),
+    ];
+
+    println!("{} scopes, {} requests per case", SCOPES + 5, ITERATIONS);
+    for (name, path) in &cases {
+        println!("{:<10} {:>10.2?}/req", name, measure(&client, path).await);
+    }
+
+    client.shutdown().await;
+}
@@ -0,0 +1,60 @@
+//! Request throughput against an app with many routes.
+//!
+//! Run with `cargo bench --features testing --bench routing`.
+
+use std::time::{Duration, Instant};
+
+use bolt_web::{App, request::RequestBody, response::ResponseWriter, testing::TestClient};
+
+const ROUTES: usize = 500;
+const WARMUP: usize = 500;
+const ITERATIONS: usize = 5_000;
+
+async fn ok(_: &mut RequestBody, res: &mut ResponseWriter) {
+    res.text("ok");
+}
+
+fn app() -> App {
+    let mut app = App::new();
+    app.set_banner(false);
+
+    for i in 0..ROUTES {
+        app.get(&format!("/static/{}/leaf", i), ok);
+        app.get(&format!("/users/{}/:id", i), ok);
+        app.get(&format!("/files/{}/*path", i), ok);
+    }
+
+    app
+}
+
+async fn measure(client: &TestClient, path: &str) -> Duration {
+    for _ in 0..WARMUP {
+        client.get(path).await.unwrap();
+    }
+
+    let started = Instant::now();
+    for _ in 0..ITERATIONS {
+        let res = client.get(path).await.unwrap();
+        assert_eq!(res.status, 200);
+    }
+    started.elapsed() / ITERATIONS as u32
+}
+
+#[tokio::main]
+async fn main() {
+    let client = TestClient::start(app()).await.unwrap();
+    let last = ROUTES - 1;
+
+    let cases = [
+        ("static", format!("/static/{}/leaf", last)),
+        ("param", format!("/users/{}/42", last)),
+        ("wildcard", format!("/files/{}/a/b/c", last)),
+    ];
+
+    println!("{} routes, {} requests per case", ROUTES * 3, ITERATIONS);
+    for (name, path) in &cases {
+        println!("{:<10} {:>10.2?}/req", name, measure(&client, path).await);
+    }
+
+    client.shutdown().await;
+}
@@ -0,0 +1,91 @@
+use std::pin::Pin;
+use std::task::{Context, Poll};
+use std::time::Duration;
+
+use bytes::Bytes;
+use hyper::body::{Body, Frame, Incoming, SizeHint};
+use pin_project_lite::pin_project;
+use tokio::time::Sleep;
+
+use crate::{
+    http::{HttpError, StatusCode},
+    phase::{self, PhaseGuard},
+    types::{BoltError, TimeoutKind},
+};
+
+pin_project! {
+    pub struct LimitedBody {
+        #[pin]
+        inner: Incoming,
+        deadline: Pin<Box<Sleep>>,
+        limit: Option<usize>,
+        read: usize,
+        phase: Option<PhaseGuard>,
+    }
+}
+
+impl LimitedBody {
+    pub fn new(inner: Incoming, timeout: Duration, limit: Option<usize>) -> Self {
+        Self {
+            inner,
+            deadline: Box::pin(tokio::time::sleep(timeout)),
+            limit,
+            read: 0,
+            phase: phase::enter(TimeoutKind::Body),
+        }
+    }
+}
+
+pub(crate) fn too_large(limit: usize) -> BoltError {
+    Box::new(HttpError::new(
+        StatusCode::ContentTooLarge,
+        format!("Request body exceeds the limit of {} bytes", limit),
+    ))
+}
+
+impl Body for LimitedBody {
+    type Data = Bytes;
+    type Error = BoltError;
+
+    fn poll_frame(
+        self: Pin<&mut Self>,
+        context: &mut Context<'_>,
+    ) -> Poll<Option<Result<Frame<Bytes>, BoltError>>> {
+        let this = self.project();
+
+        if let Poll::Ready(frame) = this.inner.poll_frame(context) {
+            if let (Some(Ok(frame)), Some(limit)) = (&frame, *this.limit)
+                && let Some(data) = frame.data_ref()
+            {
+                *this.read += data.len();
+                if *this.read > limit {
+                    return Poll::Ready(Some(Err(too_large(limit))));
+                }
+            }
+
+            if !matches!(frame, Some(Ok(_))) {
+                *this.phase = None;
+            }
+
+            return Poll::Ready(frame.map(|f| f.map_err(|e| Box::new(e) as BoltError)));
+        }
+
+        if this.deadline.as_mut().poll(context).is_ready() {
+            *this.phase = None;
+            return Poll::Ready(Some(Err(Box::new(HttpError::new(
+                StatusCode::RequestTimeout,
+                "Request body read timeout",
+            )))));
+        }
+
+        Poll::Pending
+    }
+
+    fn is_end_stream(&self) -> bool {
+        self.inner.is_end_stream()
+    }
+
+    fn size_hint(&self) -> SizeHint {
+        self.inner.size_hint()
+    }
+}
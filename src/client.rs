@@ -1,51 +1,609 @@
+use std::{
+    collections::HashSet,
+    error::Error as StdError,
+    fmt, io,
+    path::{Path, PathBuf},
+    pin::Pin,
+    time::Duration,
+};
+
 use bytes::Bytes;
-use http_body_util::{BodyExt, Full};
-use hyper::{Method, Request};
+use futures_util::{Stream, StreamExt, TryStreamExt, stream};
+use http_body_util::{BodyExt, BodyStream, Full, StreamBody, combinators::UnsyncBoxBody};
+use hyper::{
+    HeaderMap, Method, Request, Response, StatusCode, Uri,
+    body::{Frame, Incoming},
+    header::{
+        AUTHORIZATION, CONTENT_DISPOSITION, CONTENT_LENGTH, CONTENT_TYPE, COOKIE, HeaderName,
+        HeaderValue, LOCATION,
+    },
+};
 
-use hyper::http::request::Builder;
 use hyper_tls::HttpsConnector;
 use hyper_util::client::legacy::{Client as HyperClient, connect::HttpConnector};
 use hyper_util::rt::TokioExecutor;
 use serde::Serialize;
 use serde::de::DeserializeOwned;
 use serde_json::Value;
+use tokio_util::io::ReaderStream;
+use url::Url;
+use uuid::Uuid;
+
+use crate::{
+    phase,
+    types::{BoltError, TimeoutKind},
+};
+
+type ClientBody = UnsyncBoxBody<Bytes, BoltError>;
+
+pub type ByteStream = Pin<Box<dyn Stream<Item = Result<Bytes, BoltError>> + Send>>;
+
+#[derive(Debug, Clone, Copy)]
+pub struct ClientConfig {
+    pub timeout: Option<Duration>,
+    pub connect_timeout: Option<Duration>,
+    pub max_redirects: usize,
+}
+
+impl Default for ClientConfig {
+    fn default() -> Self {
+        Self {
+            timeout: Some(Duration::from_secs(30)),
+            connect_timeout: Some(Duration::from_secs(10)),
+            max_redirects: 10,
+        }
+    }
+}
+
+#[derive(Debug)]
+pub enum ClientError {
+    Timeout,
+    ConnectTimeout,
+    ConnectionRefused,
+    Connect(String),
+    TooManyRedirects(usize),
+    RedirectLoop(String),
+    InvalidRedirect(String),
+    Status(ClientResponse),
+    Decode(ClientResponse, String),
+}
+
+impl fmt::Display for ClientError {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        match self {
+            ClientError::Timeout => f.write_str("Request timed out"),
+            ClientError::ConnectTimeout => f.write_str("Connection timed out"),
+            ClientError::ConnectionRefused => f.write_str("Connection refused"),
+            ClientError::Connect(e) => write!(f, "Connection failed: {}", e),
+            ClientError::TooManyRedirects(max) => write!(f, "More than {} redirects", max),
+            ClientError::RedirectLoop(url) => write!(f, "Redirect loop at {}", url),
+            ClientError::InvalidRedirect(location) => {
+                write!(f, "Invalid redirect location: {}", location)
+            }
+            ClientError::Status(resp) => write!(f, "Upstream responded with {}", resp.status),
+            ClientError::Decode(resp, e) => {
+                write!(
+                    f,
+                    "Failed to decode {} response as JSON: {}",
+                    resp.status, e
+                )
+            }
+        }
+    }
+}
+
+impl StdError for ClientError {}
+
+#[derive(Debug, Clone)]
+pub struct ClientResponse {
+    pub status: u16,
+    pub headers: HeaderMap,
+    pub body: Bytes,
+}
+
+/// Status and headers of a response whose body is read incrementally.
+pub struct StreamingResponse {
+    pub status: u16,
+    pub headers: HeaderMap,
+    pub body: ByteStream,
+}
+
+impl StreamingResponse {
+    pub fn is_success(&self) -> bool {
+        (200..300).contains(&self.status)
+    }
+
+    pub fn content_type(&self) -> Option<&str> {
+        self.headers.get(CONTENT_TYPE)?.to_str().ok()
+    }
+}
+
+impl ClientResponse {
+    pub fn is_success(&self) -> bool {
+        (200..300).contains(&self.status)
+    }
+
+    pub fn json<T: DeserializeOwned>(&self) -> Result<T, serde_json::Error> {
+        serde_json::from_slice(&self.body)
+    }
+
+    pub fn content_type(&self) -> Option<&str> {
+        self.headers.get(CONTENT_TYPE)?.to_str().ok()
+    }
+
+    pub fn text(&self) -> String {
+        String::from_utf8_lossy(&self.body).to_string()
+    }
+
+    fn decode<T: DeserializeOwned>(self) -> Result<T, BoltError> {
+        let resp = self.error_for_status()?;
+
+        if let Some(content_type) = resp.content_type()
+            && !content_type.to_ascii_lowercase().contains("json")
+        {
+            let reason = format!("unexpected content type {}", content_type);
+            return Err(Box::new(ClientError::Decode(resp, reason)));
+        }
+
+        let body: &[u8] = if resp.body.is_empty() {
+            b"null"
+        } else {
+            &resp.body
+        };
+
+        serde_json::from_slice(body)
+            .map_err(|e| Box::new(ClientError::Decode(resp.clone(), e.to_string())) as BoltError)
+    }
+
+    pub fn error_for_status(self) -> Result<Self, BoltError> {
+        if self.is_success() {
+            Ok(self)
+        } else {
+            Err(Box::new(ClientError::Status(self)))
+        }
+    }
+
+    pub fn filename(&self) -> Option<String> {
+        let header = self.headers.get(CONTENT_DISPOSITION)?.to_str().ok()?;
+
+        let mut plain = None;
+        let mut extended = None;
+
+        for param in header.split(';').skip(1) {
+            let Some((key, value)) = param.split_once('=') else {
+                continue;
+            };
+
+            match key.trim().to_ascii_lowercase().as_str() {
+                "filename*" => extended = decode_ext_value(value.trim()),
+                "filename" => plain = Some(value.trim().trim_matches('"').to_string()),
+                _ => {}
+            }
+        }
+
+        let name = extended.or(plain)?;
+        let name = name.rsplit(['/', '\\']).next().unwrap_or_default();
+
+        (!name.is_empty()).then(|| name.to_string())
+    }
+}
+
+enum PartBody {
+    Bytes(Bytes),
+    File(PathBuf),
+}
 
-use crate::types::BoltError;
+struct Part {
+    name: String,
+    filename: Option<String>,
+    content_type: Option<String>,
+    body: PartBody,
+}
+
+pub struct MultipartForm {
+    boundary: String,
+    parts: Vec<Part>,
+}
+
+impl MultipartForm {
+    pub fn new() -> Self {
+        Self {
+            boundary: format!("bolt-{}", Uuid::new_v4().simple()),
+            parts: Vec::new(),
+        }
+    }
+
+    pub fn text(mut self, name: &str, value: impl Into<String>) -> Self {
+        self.parts.push(Part {
+            name: name.to_string(),
+            filename: None,
+            content_type: None,
+            body: PartBody::Bytes(Bytes::from(value.into())),
+        });
+        self
+    }
+
+    pub fn bytes(
+        mut self,
+        name: &str,
+        filename: &str,
+        content_type: &str,
+        data: impl Into<Bytes>,
+    ) -> Self {
+        self.parts.push(Part {
+            name: name.to_string(),
+            filename: Some(filename.to_string()),
+            content_type: Some(content_type.to_string()),
+            body: PartBody::Bytes(data.into()),
+        });
+        self
+    }
+
+    pub fn file(mut self, name: &str, path: impl AsRef<Path>) -> Self {
+        let path = path.as_ref();
+        let filename = path
+            .file_name()
+            .map(|f| f.to_string_lossy().to_string())
+            .unwrap_or_else(|| "file".to_string());
+        let content_type = mime_guess::from_path(path)
+            .first_or_octet_stream()
+            .to_string();
+
+        self.parts.push(Part {
+            name: name.to_string(),
+            filename: Some(filename),
+            content_type: Some(content_type),
+            body: PartBody::File(path.to_path_buf()),
+        });
+        self
+    }
+
+    async fn check_files(&self) -> io::Result<()> {
+        for part in &self.parts {
+            if let PartBody::File(path) = &part.body {
+                tokio::fs::metadata(path).await?;
+            }
+        }
+        Ok(())
+    }
+
+    fn content_type(&self) -> String {
+        format!("multipart/form-data; boundary={}", self.boundary)
+    }
+
+    fn into_stream(self) -> ByteStream {
+        let mut chunks: Vec<ByteStream> = Vec::new();
+
+        for part in self.parts {
+            let mut head = format!(
+                "--{}\r\nContent-Disposition: form-data; name=\"{}\"",
+                self.boundary,
+                quote(&part.name)
+            );
+            if let Some(filename) = &part.filename {
+                head.push_str(&format!("; filename=\"{}\"", quote(filename)));
+            }
+            head.push_str("\r\n");
+            if let Some(content_type) = &part.content_type {
+                head.push_str(&format!("Content-Type: {}\r\n", content_type));
+            }
+            head.push_str("\r\n");
+
+            chunks.push(chunk(head));
+            chunks.push(match part.body {
+                PartBody::Bytes(bytes) => chunk(bytes),
+                PartBody::File(path) => Box::pin(
+                    stream::once(tokio::fs::File::open(path))
+                        .map_ok(ReaderStream::new)
+                        .try_flatten()
+                        .map_err(|e| Box::new(e) as BoltError),
+                ),
+            });
+            chunks.push(chunk("\r\n"));
+        }
+
+        chunks.push(chunk(format!("--{}--\r\n", self.boundary)));
+        Box::pin(stream::iter(chunks).flatten())
+    }
+}
+
+impl Default for MultipartForm {
+    fn default() -> Self {
+        Self::new()
+    }
+}
+
+fn chunk(bytes: impl Into<Bytes>) -> ByteStream {
+    Box::pin(stream::once(futures_util::future::ready(Ok(bytes.into()))))
+}
+
+fn quote(value: &str) -> String {
+    value
+        .replace('"', "%22")
+        .replace('\r', "%0D")
+        .replace('\n', "%0A")
+}
+
+fn decode_ext_value(value: &str) -> Option<String> {
+    let mut parts = value.splitn(3, '\'');
+    let charset = parts.next()?;
+    let _language = parts.next()?;
+    let encoded = parts.next()?;
+
+    let mut bytes = Vec::with_capacity(encoded.len());
+    let mut iter = encoded.bytes();
+
+    while let Some(b) = iter.next() {
+        if b == b'%' {
+            let hex = [iter.next()?, iter.next()?];
+            let hex = std::str::from_utf8(&hex).ok()?;
+            bytes.push(u8::from_str_radix(hex, 16).ok()?);
+        } else {
+            bytes.push(b);
+        }
+    }
+
+    if charset.eq_ignore_ascii_case("utf-8") {
+        String::from_utf8(bytes).ok()
+    } else {
+        Some(bytes.iter().map(|&b| b as char).collect())
+    }
+}
 
 #[derive(Clone)]
 #[allow(dead_code)]
 pub struct Client {
-    client: HyperClient<HttpsConnector<HttpConnector>, Full<Bytes>>,
+    client: HyperClient<HttpsConnector<HttpConnector>, ClientBody>,
+    config: ClientConfig,
 }
 
 #[allow(dead_code)]
 impl Client {
     pub fn new() -> Self {
-        let https = HttpsConnector::new();
-        let client = HyperClient::builder(TokioExecutor::new()).build::<_, Full<Bytes>>(https);
-        Self { client }
+        Self::with_config(ClientConfig::default())
+    }
+
+    pub fn with_config(config: ClientConfig) -> Self {
+        let mut http = HttpConnector::new();
+        http.enforce_http(false);
+        http.set_connect_timeout(config.connect_timeout);
+
+        let https = HttpsConnector::new_with_connector(http);
+        let client = HyperClient::builder(TokioExecutor::new()).build::<_, ClientBody>(https);
+        Self { client, config }
+    }
+
+    pub fn with_query<T: Serialize + ?Sized>(base: &str, query: &T) -> Result<String, BoltError> {
+        let mut url = Url::parse(base)?;
+        let encoded = serde_urlencoded::to_string(query)?;
+
+        if encoded.is_empty() {
+            return Ok(url.into());
+        }
+
+        let combined = match url.query() {
+            Some(existing) if !existing.is_empty() => format!("{}&{}", existing, encoded),
+            _ => encoded,
+        };
+        url.set_query(Some(&combined));
+
+        Ok(url.into())
     }
 
-    fn apply_headers(mut builder: Builder, headers: &Option<Value>) -> Builder {
-        if let Some(Value::Object(map)) = headers {
-            for (k, v) in map {
-                if let Some(s) = v.as_str() {
-                    builder = builder.header(k, s);
+    fn full(bytes: impl Into<Bytes>) -> ClientBody {
+        Full::new(bytes.into())
+            .map_err(|never| match never {})
+            .boxed_unsync()
+    }
+
+    fn header_map(headers: &Option<Value>) -> HeaderMap {
+        let mut map = HeaderMap::new();
+
+        if let Some(Value::Object(values)) = headers {
+            for (k, v) in values {
+                if let (Ok(name), Some(Ok(value))) = (
+                    HeaderName::from_bytes(k.as_bytes()),
+                    v.as_str().map(HeaderValue::from_str),
+                ) {
+                    map.insert(name, value);
                 }
             }
         }
-        builder
+        map
     }
 
-    pub async fn fetch(&self, url: &str, headers: &Option<Value>) -> Result<String, BoltError> {
-        let mut builder = Request::builder().method(Method::GET).uri(url);
-        builder = Self::apply_headers(builder, &headers);
+    async fn timed<T>(
+        &self,
+        fut: impl Future<Output = Result<T, BoltError>>,
+    ) -> Result<T, BoltError> {
+        let _upstream = phase::enter(TimeoutKind::Upstream);
+
+        match self.config.timeout {
+            Some(limit) => tokio::time::timeout(limit, fut)
+                .await
+                .map_err(|_| Box::new(ClientError::Timeout) as BoltError)?,
+            None => fut.await,
+        }
+    }
+
+    async fn dispatch(&self, req: Request<ClientBody>) -> Result<Response<Incoming>, BoltError> {
+        self.client.request(req).await.map_err(|e| {
+            if !e.is_connect() {
+                return Box::new(e) as BoltError;
+            }
+
+            let mut source = e.source();
+            while let Some(inner) = source {
+                if let Some(io) = inner.downcast_ref::<io::Error>() {
+                    match io.kind() {
+                        io::ErrorKind::TimedOut => return Box::new(ClientError::ConnectTimeout),
+                        io::ErrorKind::ConnectionRefused => {
+                            return Box::new(ClientError::ConnectionRefused);
+                        }
+                        _ => {}
+                    }
+                }
+                source = inner.source();
+            }
+
+            Box::new(ClientError::Connect(e.to_string()))
+        })
+    }
+
+    async fn execute(
+        &self,
+        mut method: Method,
+        url: &str,
+        mut headers: HeaderMap,
+        mut body: Bytes,
+    ) -> Result<Response<Incoming>, BoltError> {
+        let mut current: Uri = url.parse()?;
+        let mut visited = HashSet::new();
+
+        loop {
+            let mut req = Request::builder()
+                .method(method.clone())
+                .uri(current.clone())
+                .body(Self::full(body.clone()))?;
+            *req.headers_mut() = headers.clone();
+
+            let resp = self.dispatch(req).await?;
+
+            if self.config.max_redirects == 0 || !resp.status().is_redirection() {
+                return Ok(resp);
+            }
+
+            let Some(location) = resp.headers().get(LOCATION) else {
+                return Ok(resp);
+            };
+
+            let next = resolve_location(&current, location)?;
+
+            visited.insert((method.clone(), current.to_string()));
+            if visited.len() > self.config.max_redirects {
+                return Err(Box::new(ClientError::TooManyRedirects(
+                    self.config.max_redirects,
+                )));
+            }
+
+            let status = resp.status();
+            if status == StatusCode::SEE_OTHER
+                || (matches!(status, StatusCode::MOVED_PERMANENTLY | StatusCode::FOUND)
+                    && method == Method::POST)
+            {
+                if method != Method::HEAD {
+                    method = Method::GET;
+                }
+                body = Bytes::new();
+                headers.remove(CONTENT_TYPE);
+                headers.remove(CONTENT_LENGTH);
+            }
+
+            if next.host() != current.host() || next.port_u16() != current.port_u16() {
+                headers.remove(AUTHORIZATION);
+                headers.remove(COOKIE);
+            }
+
+            if visited.contains(&(method.clone(), next.to_string())) {
+                return Err(Box::new(ClientError::RedirectLoop(next.to_string())));
+            }
+
+            current = next;
+        }
+    }
+
+    async fn call(
+        &self,
+        method: Method,
+        url: &str,
+        headers: HeaderMap,
+        body: Bytes,
+    ) -> Result<ClientResponse, BoltError> {
+        self.timed(async {
+            let resp = self.execute(method, url, headers, body).await?;
+            let (parts, body) = resp.into_parts();
+
+            Ok(ClientResponse {
+                status: parts.status.as_u16(),
+                headers: parts.headers,
+                body: body.collect().await?.to_bytes(),
+            })
+        })
+        .await
+    }
+
+    pub async fn send(
+        &self,
+        method: Method,
+        url: &str,
+        body: impl Into<Bytes>,
+        headers: &Option<Value>,
+    ) -> Result<ClientResponse, BoltError> {
+        self.call(method, url, Self::header_map(headers), body.into())
+            .await
+    }
+
+    pub async fn post_form<T: Serialize + ?Sized>(
+        &self,
+        url: &str,
+        form: &T,
+        headers: &Option<Value>,
+    ) -> Result<ClientResponse, BoltError> {
+        let body = serde_urlencoded::to_string(form)?;
+
+        let mut map = Self::header_map(headers);
+        map.insert(
+            CONTENT_TYPE,
+            HeaderValue::from_static("application/x-www-form-urlencoded"),
+        );
+
+        self.call(Method::POST, url, map, body.into()).await
+    }
+
+    pub async fn post_multipart(
+        &self,
+        url: &str,
+        form: MultipartForm,
+        headers: &Option<Value>,
+    ) -> Result<ClientResponse, BoltError> {
+        form.check_files().await?;
+
+        let mut map = Self::header_map(headers);
+        map.insert(CONTENT_TYPE, HeaderValue::from_str(&form.content_type())?);
 
-        let req = builder.body(Full::new(Bytes::new()))?;
-        let resp = self.client.request(req).await?;
-        let body = resp.into_body().collect().await?.to_bytes();
+        let body = StreamBody::new(form.into_stream().map_ok(Frame::data)).boxed_unsync();
+        let mut req = Request::builder()
+            .method(Method::POST)
+            .uri(url)
+            .body(body)?;
+        *req.headers_mut() = map;
+
+        self.timed(async {
+            let (parts, body) = self.dispatch(req).await?.into_parts();
+
+            Ok(ClientResponse {
+                status: parts.status.as_u16(),
+                headers: parts.headers,
+                body: body.collect().await?.to_bytes(),
+            })
+        })
+        .await
+    }
 
-        Ok(String::from_utf8_lossy(&body).to_string())
+    pub async fn get_raw(&self, url: &str, headers: &Option<Value>) -> Result<Bytes, BoltError> {
+        let resp = self
+            .call(Method::GET, url, Self::header_map(headers), Bytes::new())
+            .await?;
+
+        Ok(resp.error_for_status()?.body)
+    }
+
+    pub async fn fetch(&self, url: &str, headers: &Option<Value>) -> Result<String, BoltError> {
+        let resp = self
+            .call(Method::GET, url, Self::header_map(headers), Bytes::new())
+            .await?;
+
+        Ok(resp.text())
     }
 
     async fn send_json<T: Serialize + ?Sized, U: DeserializeOwned>(
@@ -57,18 +615,13 @@ impl Client {
     ) -> Result<U, BoltError> {
         let body_bytes = serde_json::to_vec(body)?;
 
-        let mut builder = Request::builder()
-            .method(method)
-            .uri(url)
-            .header("Content-Type", "application/json");
-
-        builder = Self::apply_headers(builder, &headers);
+        let mut map = Self::header_map(headers);
+        map.entry(CONTENT_TYPE)
+            .or_insert(HeaderValue::from_static("application/json"));
 
-        let req = builder.body(Full::new(Bytes::from(body_bytes)))?;
-        let resp = self.client.request(req).await?;
-        let bytes = resp.into_body().collect().await?.to_bytes();
+        let resp = self.call(method, url, map, body_bytes.into()).await?;
 
-        Ok(serde_json::from_slice(&bytes)?)
+        resp.decode()
     }
 
     pub async fn get<T: DeserializeOwned>(
@@ -76,14 +629,11 @@ impl Client {
         url: &str,
         headers: &Option<Value>,
     ) -> Result<T, BoltError> {
-        let mut builder = Request::builder().method(Method::GET).uri(url);
-        builder = Self::apply_headers(builder, &headers);
-
-        let req = builder.body(Full::new(Bytes::new()))?;
-        let resp = self.client.request(req).await?;
-        let body = resp.into_body().collect().await?.to_bytes();
+        let resp = self
+            .call(Method::GET, url, Self::header_map(headers), Bytes::new())
+            .await?;
 
-        Ok(serde_json::from_slice(&body)?)
+        resp.decode()
     }
 
     pub async fn post<T: Serialize + ?Sized, U: DeserializeOwned>(
@@ -118,16 +668,11 @@ impl Client {
         url: &str,
         headers: &Option<Value>,
     ) -> Result<U, BoltError> {
-        let mut builder = Request::builder().method(Method::DELETE).uri(url);
-        builder = Self::apply_headers(builder, &headers);
-
-        let req = builder.body(Full::new(Bytes::new()))?;
-
-        let resp = self.client.request(req).await?;
-
-        let body_bytes = resp.into_body().collect().await?.to_bytes();
+        let resp = self
+            .call(Method::DELETE, url, Self::header_map(headers), Bytes::new())
+            .await?;
 
-        Ok(serde_json::from_slice(&body_bytes)?)
+        resp.decode()
     }
 
     pub async fn delete_with_payload<T: Serialize + ?Sized, U: DeserializeOwned>(
@@ -138,4 +683,97 @@ impl Client {
     ) -> Result<U, BoltError> {
         self.send_json(Method::DELETE, url, body, headers).await
     }
+
+    async fn send_empty(
+        &self,
+        method: Method,
+        url: &str,
+        headers: &Option<Value>,
+    ) -> Result<u16, BoltError> {
+        let resp = self
+            .call(method, url, Self::header_map(headers), Bytes::new())
+            .await?;
+
+        Ok(resp.status)
+    }
+
+    pub async fn head(&self, url: &str, headers: &Option<Value>) -> Result<u16, BoltError> {
+        self.send_empty(Method::HEAD, url, headers).await
+    }
+
+    pub async fn options(&self, url: &str, headers: &Option<Value>) -> Result<u16, BoltError> {
+        self.send_empty(Method::OPTIONS, url, headers).await
+    }
+
+    pub async fn download(
+        &self,
+        url: &str,
+        headers: &Option<Value>,
+    ) -> Result<ClientResponse, BoltError> {
+        self.call(Method::GET, url, Self::header_map(headers), Bytes::new())
+            .await
+    }
+
+    pub async fn get_stream(
+        &self,
+        url: &str,
+        headers: &Option<Value>,
+    ) -> Result<StreamingResponse, BoltError> {
+        let resp = self
+            .timed(self.execute(Method::GET, url, Self::header_map(headers), Bytes::new()))
+            .await?;
+
+        Ok(Self::into_streaming(resp))
+    }
+
+    pub async fn send_stream<S>(
+        &self,
+        method: Method,
+        url: &str,
+        body: S,
+        headers: &Option<Value>,
+    ) -> Result<StreamingResponse, BoltError>
+    where
+        S: Stream<Item = Result<Bytes, BoltError>> + Send + 'static,
+    {
+        let body = StreamBody::new(body.map_ok(Frame::data)).boxed_unsync();
+        let mut req = Request::builder().method(method).uri(url).body(body)?;
+        *req.headers_mut() = Self::header_map(headers);
+
+        let resp = self.timed(self.dispatch(req)).await?;
+
+        Ok(Self::into_streaming(resp))
+    }
+
+    fn into_streaming(resp: Response<Incoming>) -> StreamingResponse {
+        let (parts, body) = resp.into_parts();
+
+        StreamingResponse {
+            status: parts.status.as_u16(),
+            headers: parts.headers,
+            body: Self::into_stream(body),
+        }
+    }
+
+    fn into_stream(body: Incoming) -> ByteStream {
+        Box::pin(
+            BodyStream::new(body)
+                .try_filter_map(|frame| async move { Ok(frame.into_data().ok()) })
+                .map_err(|e| Box::new(e) as BoltError),
+        )
+    }
+}
+
+fn resolve_location(current: &Uri, location: &HeaderValue) -> Result<Uri, BoltError> {
+    let raw = location.to_str().unwrap_or_default();
+    let invalid = || Box::new(ClientError::InvalidRedirect(raw.to_string())) as BoltError;
+
+    let base = Url::parse(&current.to_string()).map_err(|_| invalid())?;
+    let next = base.join(raw).map_err(|_| invalid())?;
+
+    if !matches!(next.scheme(), "http" | "https") {
+        return Err(invalid());
+    }
+
+    next.as_str().parse().map_err(|_| invalid())
 }
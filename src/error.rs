@@ -1,6 +1,7 @@
+use async_trait::async_trait;
 use serde_json::json;
 
-use crate::{error, response::ResponseWriter};
+use crate::{error, http::ProblemDetails, response::ResponseWriter, types::ErrorHandler};
 
 async fn default(message: String, res: &mut ResponseWriter) {
     let status = res.get_code(res.status);
@@ -11,8 +12,27 @@ async fn default(message: String, res: &mut ResponseWriter) {
         message
     };
 
-    res.status(res.status)
-        .json(&json!({"message": msg , "status" : status }));
+    let body = match res.request_id() {
+        Some(id) => json!({"message": msg , "status" : status, "request_id": id }),
+        None => json!({"message": msg , "status" : status }),
+    };
+
+    res.status(res.status).json(&body);
 }
 
 error!(default);
+
+pub struct ProblemJson;
+
+#[async_trait]
+impl ErrorHandler for ProblemJson {
+    async fn run(&self, message: String, res: &mut ResponseWriter) {
+        let mut problem = ProblemDetails::new(res.status);
+
+        if res.status.as_u16() < 500 && !message.is_empty() {
+            problem = problem.detail(message);
+        }
+
+        res.problem(&problem);
+    }
+}
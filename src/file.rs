@@ -0,0 +1,485 @@
+use std::{
+    collections::HashMap,
+    hash::{DefaultHasher, Hash, Hasher},
+    io::{self, Write},
+    path::{Path, PathBuf},
+    sync::{Mutex, OnceLock},
+    time::{Duration, SystemTime, UNIX_EPOCH},
+};
+
+use async_trait::async_trait;
+use bytes::Bytes;
+use flate2::{
+    Compression,
+    write::{DeflateEncoder, GzEncoder},
+};
+
+use hyper::{
+    HeaderMap, Method,
+    header::{
+        ACCEPT_ENCODING, IF_MATCH, IF_MODIFIED_SINCE, IF_NONE_MATCH, IF_RANGE, IF_UNMODIFIED_SINCE,
+        RANGE,
+    },
+};
+
+use tokio::{fs, io::AsyncReadExt};
+
+use percent_encoding::percent_decode_str;
+
+use crate::{
+    http::StatusCode,
+    request::{RequestBody, parse_quality_list},
+    response::ResponseWriter,
+    types::Handler,
+};
+
+const MAX_COMPRESS_LEN: u64 = 4 * 1024 * 1024;
+
+const MAX_COMPRESSED_CACHE: usize = 32 * 1024 * 1024;
+
+type CompressedKey = (PathBuf, Encoding);
+
+#[derive(Default)]
+struct CompressedCache {
+    entries: HashMap<CompressedKey, CompressedEntry>,
+    size: usize,
+    clock: u64,
+}
+
+struct CompressedEntry {
+    modified: SystemTime,
+    bytes: Bytes,
+    used: u64,
+}
+
+impl CompressedCache {
+    fn get(&mut self, key: &CompressedKey, modified: SystemTime) -> Option<Bytes> {
+        self.clock += 1;
+        let entry = self
+            .entries
+            .get_mut(key)
+            .filter(|e| e.modified == modified)?;
+        entry.used = self.clock;
+        Some(entry.bytes.clone())
+    }
+
+    fn insert(&mut self, key: CompressedKey, modified: SystemTime, bytes: Bytes) {
+        if bytes.len() > MAX_COMPRESSED_CACHE {
+            return;
+        }
+
+        self.clock += 1;
+        self.size += bytes.len();
+        let entry = CompressedEntry {
+            modified,
+            bytes,
+            used: self.clock,
+        };
+
+        if let Some(old) = self.entries.insert(key, entry) {
+            self.size -= old.bytes.len();
+        }
+
+        while self.size > MAX_COMPRESSED_CACHE {
+            let Some(oldest) = self
+                .entries
+                .iter()
+                .min_by_key(|(_, e)| e.used)
+                .map(|(k, _)| k.clone())
+            else {
+                break;
+            };
+
+            if let Some(old) = self.entries.remove(&oldest) {
+                self.size -= old.bytes.len();
+            }
+        }
+    }
+}
+
+static COMPRESSED: OnceLock<Mutex<CompressedCache>> = OnceLock::new();
+
+#[derive(Debug, Clone)]
+pub struct FileOptions {
+    pub etag: bool,
+    pub last_modified: bool,
+    pub ranges: bool,
+    pub cache_control: Option<String>,
+    pub compress: bool,
+}
+
+impl Default for FileOptions {
+    fn default() -> Self {
+        Self {
+            etag: true,
+            last_modified: true,
+            ranges: true,
+            cache_control: None,
+            compress: true,
+        }
+    }
+}
+
+#[derive(Debug, Clone)]
+pub struct Asset {
+    pub data: Bytes,
+    pub modified: Option<SystemTime>,
+}
+
+pub trait AssetSource: Send + Sync {
+    fn get(&self, path: &str) -> Option<Asset>;
+}
+
+#[derive(Debug, Clone, Default)]
+pub struct EmbeddedAssets {
+    files: HashMap<String, Bytes>,
+}
+
+impl EmbeddedAssets {
+    pub fn new(files: &[(&str, &'static [u8])]) -> Self {
+        Self {
+            files: files
+                .iter()
+                .map(|(path, data)| (normalize(path), Bytes::from_static(data)))
+                .collect(),
+        }
+    }
+
+    pub fn insert(&mut self, path: &str, data: impl Into<Bytes>) {
+        self.files.insert(normalize(path), data.into());
+    }
+}
+
+impl AssetSource for EmbeddedAssets {
+    fn get(&self, path: &str) -> Option<Asset> {
+        self.files.get(&normalize(path)).map(|data| Asset {
+            data: data.clone(),
+            modified: None,
+        })
+    }
+}
+
+pub(crate) struct StaticDir {
+    root: PathBuf,
+    opts: FileOptions,
+}
+
+impl StaticDir {
+    pub(crate) fn new(root: &str, opts: FileOptions) -> Self {
+        Self {
+            root: PathBuf::from(root),
+            opts,
+        }
+    }
+
+    async fn resolve(&self, path: &str) -> Option<PathBuf> {
+        let path = percent_decode_str(path).decode_utf8().ok()?;
+
+        if path.contains('\0') {
+            return None;
+        }
+
+        let root = fs::canonicalize(&self.root).await.ok()?;
+        let target = fs::canonicalize(root.join(path.trim_start_matches('/')))
+            .await
+            .ok()?;
+
+        if !target.starts_with(&root) {
+            return None;
+        }
+
+        if fs::metadata(&target).await.ok()?.is_dir() {
+            let index = target.join("index.html");
+            return fs::metadata(&index)
+                .await
+                .is_ok_and(|m| m.is_file())
+                .then_some(index);
+        }
+
+        Some(target)
+    }
+}
+
+#[async_trait]
+impl Handler for StaticDir {
+    async fn run(&self, req: &mut RequestBody, res: &mut ResponseWriter) {
+        match self.resolve(&req.param("path")).await {
+            Some(path) => res.serve_file(req, path, self.opts.clone()).await,
+            None => {
+                res.error(StatusCode::NotFound, "File not found");
+            }
+        }
+    }
+}
+
+fn normalize(path: &str) -> String {
+    path.trim_start_matches('/').to_string()
+}
+
+pub(crate) enum Precondition {
+    Proceed,
+    NotModified,
+    Failed,
+}
+
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Hash)]
+pub(crate) enum Encoding {
+    Brotli,
+    Gzip,
+    Deflate,
+}
+
+impl Encoding {
+    pub(crate) fn as_str(&self) -> &'static str {
+        match self {
+            Encoding::Brotli => "br",
+            Encoding::Gzip => "gzip",
+            Encoding::Deflate => "deflate",
+        }
+    }
+
+    pub(crate) fn encode(&self, data: &[u8]) -> io::Result<Vec<u8>> {
+        match self {
+            Encoding::Brotli => {
+                let mut encoder = brotli::CompressorWriter::new(Vec::new(), 4096, 5, 22);
+                encoder.write_all(data)?;
+                Ok(encoder.into_inner())
+            }
+            Encoding::Gzip => {
+                let mut encoder = GzEncoder::new(Vec::new(), Compression::default());
+                encoder.write_all(data)?;
+                encoder.finish()
+            }
+            Encoding::Deflate => {
+                let mut encoder = DeflateEncoder::new(Vec::new(), Compression::default());
+                encoder.write_all(data)?;
+                encoder.finish()
+            }
+        }
+    }
+}
+
+pub(crate) fn compressible(mime: &str, len: u64) -> bool {
+    if len == 0 || len > MAX_COMPRESS_LEN {
+        return false;
+    }
+
+    let mime = mime.split(';').next().unwrap_or_default().trim();
+
+    mime.starts_with("text/")
+        || mime.ends_with("+json")
+        || mime.ends_with("+xml")
+        || matches!(
+            mime,
+            "application/json"
+                | "application/javascript"
+                | "application/xml"
+                | "application/wasm"
+                | "image/svg+xml"
+        )
+}
+
+pub(crate) fn negotiate(req: &RequestBody) -> Option<Encoding> {
+    let header = header(req.headers(), ACCEPT_ENCODING)?;
+
+    parse_quality_list(header)
+        .into_iter()
+        .find_map(|(name, _)| match name.as_str() {
+            "br" => Some(Encoding::Brotli),
+            "gzip" | "x-gzip" | "*" => Some(Encoding::Gzip),
+            "deflate" => Some(Encoding::Deflate),
+            _ => None,
+        })
+}
+
+pub(crate) fn encoded_etag(etag: &str, encoding: Encoding) -> String {
+    format!("{}-{}\"", etag.trim_end_matches('"'), encoding.as_str())
+}
+
+pub(crate) async fn compressed(
+    path: &Path,
+    encoding: Encoding,
+    modified: Option<SystemTime>,
+    file: &mut fs::File,
+) -> io::Result<Bytes> {
+    let cache = COMPRESSED.get_or_init(|| Mutex::new(CompressedCache::default()));
+    let key = (path.to_path_buf(), encoding);
+
+    if let Some(m) = modified
+        && let Ok(mut entries) = cache.lock()
+        && let Some(bytes) = entries.get(&key, m)
+    {
+        return Ok(bytes);
+    }
+
+    let mut data = Vec::new();
+    file.read_to_end(&mut data).await?;
+
+    let bytes = tokio::task::spawn_blocking(move || encoding.encode(&data))
+        .await
+        .map_err(io::Error::other)?
+        .map(Bytes::from)?;
+
+    if let Some(m) = modified
+        && let Ok(mut entries) = cache.lock()
+    {
+        entries.insert(key, m, bytes.clone());
+    }
+
+    Ok(bytes)
+}
+
+pub(crate) fn etag(len: u64, modified: SystemTime) -> String {
+    let millis = modified
+        .duration_since(UNIX_EPOCH)
+        .map(|d| d.as_millis())
+        .unwrap_or_default();
+
+    format!("\"{:x}-{:x}\"", len, millis)
+}
+
+pub(crate) fn content_etag(data: &[u8]) -> String {
+    let mut hasher = DefaultHasher::new();
+    data.hash(&mut hasher);
+
+    format!("\"{:x}-{:x}\"", data.len(), hasher.finish())
+}
+
+pub(crate) fn evaluate(
+    req: &RequestBody,
+    etag: Option<&str>,
+    modified: Option<SystemTime>,
+) -> Precondition {
+    let headers = req.headers();
+    let safe = req.method() == Method::GET || req.method() == Method::HEAD;
+    let modified = modified.map(truncate);
+
+    if let Some(list) = header(headers, IF_MATCH) {
+        if !etag_matches(list, etag, false) {
+            return Precondition::Failed;
+        }
+    } else if let (Some(since), Some(m)) = (date(headers, IF_UNMODIFIED_SINCE), modified)
+        && m > since
+    {
+        return Precondition::Failed;
+    }
+
+    if let Some(list) = header(headers, IF_NONE_MATCH) {
+        if etag_matches(list, etag, true) {
+            return if safe {
+                Precondition::NotModified
+            } else {
+                Precondition::Failed
+            };
+        }
+    } else if let (Some(since), Some(m)) = (date(headers, IF_MODIFIED_SINCE), modified)
+        && safe
+        && m <= since
+    {
+        return Precondition::NotModified;
+    }
+
+    Precondition::Proceed
+}
+
+pub(crate) fn range(
+    req: &RequestBody,
+    etag: Option<&str>,
+    modified: Option<SystemTime>,
+    len: u64,
+) -> Option<Result<(u64, u64), ()>> {
+    let headers = req.headers();
+    let spec = header(headers, RANGE)?;
+
+    if let Some(validator) = header(headers, IF_RANGE) {
+        let fresh = if validator.starts_with('"') {
+            etag == Some(validator)
+        } else {
+            match (httpdate::parse_http_date(validator), modified) {
+                (Ok(date), Some(m)) => truncate(m) == date,
+                _ => false,
+            }
+        };
+
+        if !fresh {
+            return None;
+        }
+    }
+
+    parse_range(spec, len)
+}
+
+fn parse_range(spec: &str, len: u64) -> Option<Result<(u64, u64), ()>> {
+    let spec = spec.trim().strip_prefix("bytes=")?;
+
+    if spec.contains(',') {
+        return None;
+    }
+
+    let (start, end) = spec.split_once('-')?;
+    let (start, end) = (start.trim(), end.trim());
+
+    if start.is_empty() {
+        let suffix: u64 = end.parse().ok()?;
+        if suffix == 0 || len == 0 {
+            return Some(Err(()));
+        }
+        return Some(Ok((len.saturating_sub(suffix), len)));
+    }
+
+    let start: u64 = start.parse().ok()?;
+    if start >= len {
+        return Some(Err(()));
+    }
+
+    let end = if end.is_empty() {
+        len
+    } else {
+        let last: u64 = end.parse().ok()?;
+        if last < start {
+            return None;
+        }
+        last.saturating_add(1).min(len)
+    };
+
+    if start >= end {
+        return Some(Err(()));
+    }
+
+    Some(Ok((start, end)))
+}
+
+fn etag_matches(list: &str, etag: Option<&str>, weak: bool) -> bool {
+    if list.trim() == "*" {
+        return true;
+    }
+
+    let Some(etag) = etag else {
+        return false;
+    };
+
+    list.split(',').map(|t| t.trim()).any(|tag| {
+        if weak {
+            tag.trim_start_matches("W/") == etag.trim_start_matches("W/")
+        } else {
+            !tag.starts_with("W/") && !etag.starts_with("W/") && tag == etag
+        }
+    })
+}
+
+fn header(headers: &HeaderMap, name: hyper::header::HeaderName) -> Option<&str> {
+    headers.get(name).and_then(|v| v.to_str().ok())
+}
+
+fn date(headers: &HeaderMap, name: hyper::header::HeaderName) -> Option<SystemTime> {
+    header(headers, name).and_then(|v| httpdate::parse_http_date(v).ok())
+}
+
+fn truncate(time: SystemTime) -> SystemTime {
+    let secs = time
+        .duration_since(UNIX_EPOCH)
+        .map(|d| d.as_secs())
+        .unwrap_or_default();
+
+    UNIX_EPOCH + Duration::from_secs(secs)
+}
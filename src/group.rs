@@ -2,12 +2,15 @@ use std::sync::Arc;
 
 use crate::{
     App,
-    types::{Handler, Method, Middleware},
+    request::RequestBody,
+    response::ResponseWriter,
+    types::{Handler, HandlerFuture, Method, Middleware},
 };
 
 #[allow(dead_code)]
 pub struct Group<'a> {
     pub prefix: String,
+    pub host: Option<String>,
     pub app: &'a mut App,
 }
 
@@ -17,52 +20,127 @@ impl<'a> Group<'a> {
     where
         H: Handler + 'static,
     {
-        let full_path = format!("{}{}", self.prefix, path);
-        self.app.add_route(Method::GET, &full_path, handler);
+        self.add_route(Method::GET, path, handler);
     }
 
     pub fn post<H>(&mut self, path: &str, handler: H)
     where
         H: Handler + 'static,
     {
-        let full_path = format!("{}{}", self.prefix, path);
-        self.app.add_route(Method::POST, &full_path, handler);
+        self.add_route(Method::POST, path, handler);
     }
 
     pub fn put<H>(&mut self, path: &str, handler: H)
     where
         H: Handler + 'static,
     {
-        let full_path = format!("{}{}", self.prefix, path);
-        self.app.add_route(Method::PUT, &full_path, handler);
+        self.add_route(Method::PUT, path, handler);
     }
 
     pub fn patch<H>(&mut self, path: &str, handler: H)
     where
         H: Handler + 'static,
     {
-        let full_path = format!("{}{}", self.prefix, path);
-        self.app.add_route(Method::PATCH, &full_path, handler);
+        self.add_route(Method::PATCH, path, handler);
     }
 
     pub fn delete<H>(&mut self, path: &str, handler: H)
+    where
+        H: Handler + 'static,
+    {
+        self.add_route(Method::DELETE, path, handler);
+    }
+
+    pub fn head<H>(&mut self, path: &str, handler: H)
+    where
+        H: Handler + 'static,
+    {
+        self.add_route(Method::HEAD, path, handler);
+    }
+
+    pub fn options<H>(&mut self, path: &str, handler: H)
+    where
+        H: Handler + 'static,
+    {
+        self.add_route(Method::OPTIONS, path, handler);
+    }
+
+    pub fn get_fn<F>(&mut self, path: &str, handler: F)
+    where
+        F: for<'r> Fn(&'r mut RequestBody, &'r mut ResponseWriter) -> HandlerFuture<'r>
+            + Send
+            + Sync
+            + 'static,
+    {
+        self.add_route(Method::GET, path, handler);
+    }
+
+    pub fn post_fn<F>(&mut self, path: &str, handler: F)
+    where
+        F: for<'r> Fn(&'r mut RequestBody, &'r mut ResponseWriter) -> HandlerFuture<'r>
+            + Send
+            + Sync
+            + 'static,
+    {
+        self.add_route(Method::POST, path, handler);
+    }
+
+    pub fn put_fn<F>(&mut self, path: &str, handler: F)
+    where
+        F: for<'r> Fn(&'r mut RequestBody, &'r mut ResponseWriter) -> HandlerFuture<'r>
+            + Send
+            + Sync
+            + 'static,
+    {
+        self.add_route(Method::PUT, path, handler);
+    }
+
+    pub fn patch_fn<F>(&mut self, path: &str, handler: F)
+    where
+        F: for<'r> Fn(&'r mut RequestBody, &'r mut ResponseWriter) -> HandlerFuture<'r>
+            + Send
+            + Sync
+            + 'static,
+    {
+        self.add_route(Method::PATCH, path, handler);
+    }
+
+    pub fn delete_fn<F>(&mut self, path: &str, handler: F)
+    where
+        F: for<'r> Fn(&'r mut RequestBody, &'r mut ResponseWriter) -> HandlerFuture<'r>
+            + Send
+            + Sync
+            + 'static,
+    {
+        self.add_route(Method::DELETE, path, handler);
+    }
+
+    fn add_route<H>(&mut self, method: Method, path: &str, handler: H)
     where
         H: Handler + 'static,
     {
         let full_path = format!("{}{}", self.prefix, path);
-        self.app.add_route(Method::DELETE, &full_path, handler);
+        self.app
+            .router_for(self.host.as_deref())
+            .insert(&full_path, method, handler);
     }
 
     pub fn middleware(&mut self, path: &str, method: Option<Method>, mw: Arc<dyn Middleware>) {
-        use crate::types::Method::*;
-
         let full_path: String = format!("{}{}", self.prefix, path);
 
         match method {
-            Some(m) => self.app.router.insert_middleware(&full_path, m, mw.clone()),
+            Some(m) => self.app.router_for(self.host.as_deref()).insert_middleware(
+                &full_path,
+                m,
+                mw.clone(),
+            ),
             None => {
-                for m in [GET, POST, PUT, PATCH, DELETE, OPTIONS, HEAD, TRACE] {
-                    self.app.router.insert_middleware(&full_path, m, mw.clone());
+                for m in Method::ALL {
+                    self.app.router_for(self.host.as_deref()).insert_middleware(
+                        &full_path,
+                        m,
+                        mw.clone(),
+                    );
                 }
             }
         }
@@ -76,6 +154,7 @@ impl<'a> Group<'a> {
         let new_prefix = format!("{}/{}", base, child_path);
         Group {
             prefix: new_prefix,
+            host: self.host.clone(),
             app: self.app,
         }
     }
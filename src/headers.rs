@@ -1,29 +1,43 @@
 use pin_project_lite::pin_project;
 use std::io;
 use std::pin::Pin;
+use std::sync::{
+    Arc,
+    atomic::{AtomicBool, Ordering},
+};
 use std::task::{Context, Poll};
+use std::time::Duration;
 use tokio::io::{AsyncRead, AsyncWrite, ReadBuf};
+use tokio::time::{Instant, Sleep};
+
+const H2_PREFACE: &[u8] = b"PRI * HTTP/2.0";
 
 pin_project! {
-    pub struct LimitReader<T> {
+    pub struct HeaderTimeout<T> {
         #[pin]
         inner: T,
-        max: usize,
-        read: usize,
+        timeout: Duration,
+        reading: Arc<AtomicBool>,
+        sleep: Option<Pin<Box<Sleep>>>,
+        first: bool,
+        disabled: bool,
     }
 }
 
-impl<T: AsyncRead> LimitReader<T> {
-    pub fn new(inner: T, max: usize) -> Self {
+impl<T> HeaderTimeout<T> {
+    pub fn new(inner: T, timeout: Duration, reading: Arc<AtomicBool>) -> Self {
         Self {
             inner,
-            max,
-            read: 0,
+            timeout,
+            reading,
+            sleep: Some(Box::pin(tokio::time::sleep(timeout))),
+            first: true,
+            disabled: false,
         }
     }
 }
 
-impl<T: AsyncRead> AsyncRead for LimitReader<T> {
+impl<T: AsyncRead> AsyncRead for HeaderTimeout<T> {
     fn poll_read(
         self: Pin<&mut Self>,
         context: &mut Context<'_>,
@@ -31,18 +45,46 @@ impl<T: AsyncRead> AsyncRead for LimitReader<T> {
     ) -> Poll<io::Result<()>> {
         let this = self.project();
 
-        let before = this.read.clone();
+        if *this.disabled {
+            return this.inner.poll_read(context, buf);
+        }
+
+        if !this.reading.load(Ordering::Acquire) {
+            *this.sleep = None;
+        }
+
+        let before = buf.filled().len();
         let poll = this.inner.poll_read(context, buf);
 
-        if let Poll::Ready(Ok(())) = &poll {
-            let new_bytes = buf.filled().len().saturating_sub(before);
-            *this.read += new_bytes;
+        match &poll {
+            Poll::Ready(Ok(())) => {
+                let read = &buf.filled()[before..];
+
+                if *this.first && !read.is_empty() {
+                    *this.first = false;
+
+                    if read.starts_with(H2_PREFACE) {
+                        *this.disabled = true;
+                        *this.sleep = None;
+                        return poll;
+                    }
+                }
 
-            if *this.read > *this.max {
-                return Poll::Ready(Err(io::Error::new(
-                    io::ErrorKind::InvalidData,
-                    "header size limit exceeded",
-                )));
+                if !read.is_empty() && this.sleep.is_none() && this.reading.load(Ordering::Acquire)
+                {
+                    *this.sleep = Some(Box::pin(tokio::time::sleep(*this.timeout)));
+                }
+            }
+            Poll::Ready(Err(_)) => {}
+            Poll::Pending => {
+                if let Some(sleep) = this.sleep.as_mut()
+                    && sleep.as_mut().poll(context).is_ready()
+                {
+                    return Poll::Ready(Err(io::Error::new(
+                        io::ErrorKind::TimedOut,
+                        "header read timeout",
+                    )));
+                }
             }
         }
 
@@ -50,7 +92,7 @@ impl<T: AsyncRead> AsyncRead for LimitReader<T> {
     }
 }
 
-impl<T: AsyncWrite> AsyncWrite for LimitReader<T> {
+impl<T: AsyncWrite> AsyncWrite for HeaderTimeout<T> {
     fn poll_write(
         self: Pin<&mut Self>,
         context: &mut Context<'_>,
@@ -67,3 +109,73 @@ impl<T: AsyncWrite> AsyncWrite for LimitReader<T> {
         self.project().inner.poll_shutdown(context)
     }
 }
+
+pin_project! {
+    pub struct IdleTimeout<T> {
+        #[pin]
+        inner: T,
+        idle: Duration,
+        sleep: Pin<Box<Sleep>>,
+    }
+}
+
+impl<T> IdleTimeout<T> {
+    pub fn new(inner: T, idle: Duration) -> Self {
+        Self {
+            inner,
+            idle,
+            sleep: Box::pin(tokio::time::sleep(idle)),
+        }
+    }
+}
+
+impl<T: AsyncRead> AsyncRead for IdleTimeout<T> {
+    fn poll_read(
+        self: Pin<&mut Self>,
+        context: &mut Context<'_>,
+        buf: &mut ReadBuf<'_>,
+    ) -> Poll<io::Result<()>> {
+        let this = self.project();
+
+        match this.inner.poll_read(context, buf) {
+            Poll::Ready(result) => {
+                this.sleep.as_mut().reset(Instant::now() + *this.idle);
+                Poll::Ready(result)
+            }
+            Poll::Pending => {
+                if this.sleep.as_mut().poll(context).is_ready() {
+                    return Poll::Ready(Err(io::Error::new(
+                        io::ErrorKind::TimedOut,
+                        "connection idle timeout",
+                    )));
+                }
+                Poll::Pending
+            }
+        }
+    }
+}
+
+impl<T: AsyncWrite> AsyncWrite for IdleTimeout<T> {
+    fn poll_write(
+        self: Pin<&mut Self>,
+        context: &mut Context<'_>,
+        buf: &[u8],
+    ) -> Poll<io::Result<usize>> {
+        let this = self.project();
+        let poll = this.inner.poll_write(context, buf);
+
+        if poll.is_ready() {
+            this.sleep.as_mut().reset(Instant::now() + *this.idle);
+        }
+
+        poll
+    }
+
+    fn poll_flush(self: Pin<&mut Self>, context: &mut Context<'_>) -> Poll<io::Result<()>> {
+        self.project().inner.poll_flush(context)
+    }
+
+    fn poll_shutdown(self: Pin<&mut Self>, context: &mut Context<'_>) -> Poll<io::Result<()>> {
+        self.project().inner.poll_shutdown(context)
+    }
+}
@@ -1,5 +1,9 @@
+use std::fmt;
+
+use serde::Serialize;
+
 #[allow(dead_code)]
-#[derive(Clone, Copy)]
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
 pub enum StatusCode {
     Continue,           //100
     SwitchingProtocols, //101
@@ -37,12 +41,299 @@ pub enum StatusCode {
     ContentTooLarge,             //413
     URITooLong,                  //414
     UnsupportedMediaType,        //415
+    RangeNotSatisfiable,         //416
+    ImATeapot,                   //418
+    UnprocessableEntity,         //422
+    PreconditionRequired,        //428
     TooManyRequests,             //429
+    RequestHeaderFieldsTooLarge, //431
+    UnavailableForLegalReasons,  //451
+
+    InternalServerError,           //500
+    NotImplemented,                //501
+    BadGateway,                    //502
+    ServiceUnavailable,            //503
+    GatewayTimeout,                //504
+    HTTPVersionNotSupported,       //505
+    InsufficientStorage,           //507
+    NetworkAuthenticationRequired, //511
+
+    Custom(CustomCode),
+}
+
+/// A three-digit status code with no named variant, built via `ResponseWriter::status_code`.
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub struct CustomCode(u16);
+
+impl CustomCode {
+    pub(crate) fn new(code: u16) -> Option<Self> {
+        (100..=999).contains(&code).then_some(Self(code))
+    }
+}
+
+impl StatusCode {
+    pub fn as_u16(&self) -> u16 {
+        match self {
+            StatusCode::Continue => 100,
+            StatusCode::SwitchingProtocols => 101,
+            StatusCode::Processing => 102,
+            StatusCode::EarlyHints => 103,
+            StatusCode::OK => 200,
+            StatusCode::Created => 201,
+            StatusCode::Accepted => 202,
+            StatusCode::NonAuthoritativeInformation => 203,
+            StatusCode::NoContent => 204,
+            StatusCode::ResetContent => 205,
+            StatusCode::PartialContent => 206,
+            StatusCode::MovedPermanently => 301,
+            StatusCode::Found => 302,
+            StatusCode::SeeOther => 303,
+            StatusCode::NotModified => 304,
+            StatusCode::TemporaryRedirect => 307,
+            StatusCode::PermanentRedirect => 308,
+            StatusCode::BadRequest => 400,
+            StatusCode::Unauthorized => 401,
+            StatusCode::PaymentRequired => 402,
+            StatusCode::Forbidden => 403,
+            StatusCode::NotFound => 404,
+            StatusCode::MethodNotAllowed => 405,
+            StatusCode::NotAcceptable => 406,
+            StatusCode::ProxyAuthenticationRequired => 407,
+            StatusCode::RequestTimeout => 408,
+            StatusCode::Conflict => 409,
+            StatusCode::Gone => 410,
+            StatusCode::LengthRequired => 411,
+            StatusCode::PreconditionFailed => 412,
+            StatusCode::ContentTooLarge => 413,
+            StatusCode::URITooLong => 414,
+            StatusCode::UnsupportedMediaType => 415,
+            StatusCode::RangeNotSatisfiable => 416,
+            StatusCode::ImATeapot => 418,
+            StatusCode::UnprocessableEntity => 422,
+            StatusCode::PreconditionRequired => 428,
+            StatusCode::TooManyRequests => 429,
+            StatusCode::RequestHeaderFieldsTooLarge => 431,
+            StatusCode::UnavailableForLegalReasons => 451,
+            StatusCode::InternalServerError => 500,
+            StatusCode::NotImplemented => 501,
+            StatusCode::BadGateway => 502,
+            StatusCode::ServiceUnavailable => 503,
+            StatusCode::GatewayTimeout => 504,
+            StatusCode::HTTPVersionNotSupported => 505,
+            StatusCode::InsufficientStorage => 507,
+            StatusCode::NetworkAuthenticationRequired => 511,
+            StatusCode::Custom(code) => code.0,
+        }
+    }
 
-    InternalServerError,     //500
-    NotImplemented,          //501
-    BadGateway,              //502
-    ServiceUnavailable,      //503
-    GatewayTimeout,          //504
-    HTTPVersionNotSupported, //505
+    pub fn from_u16(code: u16) -> Option<StatusCode> {
+        match code {
+            100 => Some(StatusCode::Continue),
+            101 => Some(StatusCode::SwitchingProtocols),
+            102 => Some(StatusCode::Processing),
+            103 => Some(StatusCode::EarlyHints),
+            200 => Some(StatusCode::OK),
+            201 => Some(StatusCode::Created),
+            202 => Some(StatusCode::Accepted),
+            203 => Some(StatusCode::NonAuthoritativeInformation),
+            204 => Some(StatusCode::NoContent),
+            205 => Some(StatusCode::ResetContent),
+            206 => Some(StatusCode::PartialContent),
+            301 => Some(StatusCode::MovedPermanently),
+            302 => Some(StatusCode::Found),
+            303 => Some(StatusCode::SeeOther),
+            304 => Some(StatusCode::NotModified),
+            307 => Some(StatusCode::TemporaryRedirect),
+            308 => Some(StatusCode::PermanentRedirect),
+            400 => Some(StatusCode::BadRequest),
+            401 => Some(StatusCode::Unauthorized),
+            402 => Some(StatusCode::PaymentRequired),
+            403 => Some(StatusCode::Forbidden),
+            404 => Some(StatusCode::NotFound),
+            405 => Some(StatusCode::MethodNotAllowed),
+            406 => Some(StatusCode::NotAcceptable),
+            407 => Some(StatusCode::ProxyAuthenticationRequired),
+            408 => Some(StatusCode::RequestTimeout),
+            409 => Some(StatusCode::Conflict),
+            410 => Some(StatusCode::Gone),
+            411 => Some(StatusCode::LengthRequired),
+            412 => Some(StatusCode::PreconditionFailed),
+            413 => Some(StatusCode::ContentTooLarge),
+            414 => Some(StatusCode::URITooLong),
+            415 => Some(StatusCode::UnsupportedMediaType),
+            416 => Some(StatusCode::RangeNotSatisfiable),
+            418 => Some(StatusCode::ImATeapot),
+            422 => Some(StatusCode::UnprocessableEntity),
+            428 => Some(StatusCode::PreconditionRequired),
+            429 => Some(StatusCode::TooManyRequests),
+            431 => Some(StatusCode::RequestHeaderFieldsTooLarge),
+            451 => Some(StatusCode::UnavailableForLegalReasons),
+            500 => Some(StatusCode::InternalServerError),
+            501 => Some(StatusCode::NotImplemented),
+            502 => Some(StatusCode::BadGateway),
+            503 => Some(StatusCode::ServiceUnavailable),
+            504 => Some(StatusCode::GatewayTimeout),
+            505 => Some(StatusCode::HTTPVersionNotSupported),
+            507 => Some(StatusCode::InsufficientStorage),
+            511 => Some(StatusCode::NetworkAuthenticationRequired),
+            _ => None,
+        }
+    }
+
+    pub fn reason_phrase(&self) -> &'static str {
+        match self {
+            StatusCode::Continue => "Continue",
+            StatusCode::SwitchingProtocols => "Switching Protocols",
+            StatusCode::Processing => "Processing",
+            StatusCode::EarlyHints => "Early Hints",
+            StatusCode::OK => "OK",
+            StatusCode::Created => "Created",
+            StatusCode::Accepted => "Accepted",
+            StatusCode::NonAuthoritativeInformation => "Non-Authoritative Information",
+            StatusCode::NoContent => "No Content",
+            StatusCode::ResetContent => "Reset Content",
+            StatusCode::PartialContent => "Partial Content",
+            StatusCode::MovedPermanently => "Moved Permanently",
+            StatusCode::Found => "Found",
+            StatusCode::SeeOther => "See Other",
+            StatusCode::NotModified => "Not Modified",
+            StatusCode::TemporaryRedirect => "Temporary Redirect",
+            StatusCode::PermanentRedirect => "Permanent Redirect",
+            StatusCode::BadRequest => "Bad Request",
+            StatusCode::Unauthorized => "Unauthorized",
+            StatusCode::PaymentRequired => "Payment Required",
+            StatusCode::Forbidden => "Forbidden",
+            StatusCode::NotFound => "Not Found",
+            StatusCode::MethodNotAllowed => "Method Not Allowed",
+            StatusCode::NotAcceptable => "Not Acceptable",
+            StatusCode::ProxyAuthenticationRequired => "Proxy Authentication Required",
+            StatusCode::RequestTimeout => "Request Timeout",
+            StatusCode::Conflict => "Conflict",
+            StatusCode::Gone => "Gone",
+            StatusCode::LengthRequired => "Length Required",
+            StatusCode::PreconditionFailed => "Precondition Failed",
+            StatusCode::ContentTooLarge => "Content Too Large",
+            StatusCode::URITooLong => "URI Too Long",
+            StatusCode::UnsupportedMediaType => "Unsupported Media Type",
+            StatusCode::RangeNotSatisfiable => "Range Not Satisfiable",
+            StatusCode::ImATeapot => "I'm a teapot",
+            StatusCode::UnprocessableEntity => "Unprocessable Entity",
+            StatusCode::PreconditionRequired => "Precondition Required",
+            StatusCode::TooManyRequests => "Too Many Requests",
+            StatusCode::RequestHeaderFieldsTooLarge => "Request Header Fields Too Large",
+            StatusCode::UnavailableForLegalReasons => "Unavailable For Legal Reasons",
+            StatusCode::InternalServerError => "Internal Server Error",
+            StatusCode::NotImplemented => "Not Implemented",
+            StatusCode::BadGateway => "Bad Gateway",
+            StatusCode::ServiceUnavailable => "Service Unavailable",
+            StatusCode::GatewayTimeout => "Gateway Timeout",
+            StatusCode::HTTPVersionNotSupported => "HTTP Version Not Supported",
+            StatusCode::InsufficientStorage => "Insufficient Storage",
+            StatusCode::NetworkAuthenticationRequired => "Network Authentication Required",
+            StatusCode::Custom(_) => "",
+        }
+    }
 }
+
+impl TryFrom<u16> for StatusCode {
+    type Error = HttpError;
+
+    fn try_from(code: u16) -> Result<Self, Self::Error> {
+        StatusCode::from_u16(code).ok_or_else(|| {
+            HttpError::new(
+                StatusCode::InternalServerError,
+                format!("Unknown status code {}", code),
+            )
+        })
+    }
+}
+
+impl From<StatusCode> for u16 {
+    fn from(status: StatusCode) -> Self {
+        status.as_u16()
+    }
+}
+
+impl fmt::Display for StatusCode {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        write!(f, "{} {}", self.as_u16(), self.reason_phrase())
+    }
+}
+
+#[derive(Debug, Clone)]
+pub struct HttpError {
+    pub status: StatusCode,
+    pub message: String,
+}
+
+impl HttpError {
+    pub fn new(status: StatusCode, message: impl Into<String>) -> Self {
+        Self {
+            status,
+            message: message.into(),
+        }
+    }
+}
+
+impl fmt::Display for HttpError {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        f.write_str(&self.message)
+    }
+}
+
+impl std::error::Error for HttpError {}
+
+#[derive(Debug, Clone, Serialize)]
+pub struct ProblemDetails {
+    #[serde(rename = "type")]
+    pub kind: String,
+    pub title: String,
+    pub status: u16,
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub detail: Option<String>,
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub instance: Option<String>,
+}
+
+impl ProblemDetails {
+    pub fn new(status: StatusCode) -> Self {
+        Self {
+            kind: "about:blank".to_string(),
+            title: status.reason_phrase().to_string(),
+            status: status.as_u16(),
+            detail: None,
+            instance: None,
+        }
+    }
+
+    pub fn kind(mut self, kind: impl Into<String>) -> Self {
+        self.kind = kind.into();
+        self
+    }
+
+    pub fn title(mut self, title: impl Into<String>) -> Self {
+        self.title = title.into();
+        self
+    }
+
+    pub fn detail(mut self, detail: impl Into<String>) -> Self {
+        self.detail = Some(detail.into());
+        self
+    }
+
+    pub fn instance(mut self, instance: impl Into<String>) -> Self {
+        self.instance = Some(instance.into());
+        self
+    }
+}
+
+impl fmt::Display for ProblemDetails {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        match &self.detail {
+            Some(detail) => write!(f, "{}: {}", self.title, detail),
+            None => f.write_str(&self.title),
+        }
+    }
+}
+
+impl std::error::Error for ProblemDetails {}
@@ -1,53 +1,86 @@
-use futures_util::FutureExt;
+use cookie::Key;
+use futures_util::{FutureExt, StreamExt, stream::FuturesUnordered};
+use http_body_util::{BodyExt, Empty};
 
 use std::{
-    convert::Infallible, net::SocketAddr, panic::AssertUnwindSafe, pin::Pin, sync::Arc,
+    any::Any,
+    convert::Infallible,
+    io,
+    net::{IpAddr, SocketAddr},
+    panic::AssertUnwindSafe,
+    pin::Pin,
+    sync::{
+        Arc,
+        atomic::{AtomicBool, AtomicU64, Ordering},
+    },
     time::Duration,
 };
 
-use hyper::{
-    Request,
-    body::Incoming,
-    server::conn::{http1, http2},
-    service::service_fn,
+use hyper::{Request, body::Incoming, service::service_fn};
+use hyper_util::{
+    rt::{TokioExecutor, TokioIo, TokioTimer},
+    server::conn::auto,
 };
-use hyper_util::rt::{TokioExecutor, TokioIo};
+
+use tracing::{Instrument, debug, field, info, info_span, warn};
 
 use tokio::{
     io::{AsyncRead, AsyncWrite},
-    net::TcpListener,
-    sync::Semaphore,
+    net::{TcpListener, TcpStream},
+    sync::{OwnedSemaphorePermit, Semaphore, watch},
 };
 use tokio_rustls::TlsAcceptor;
 
 use crate::{
     client::Client,
     error::DefaultErrorHandler,
+    file::FileOptions,
     group::Group,
-    headers::LimitReader,
+    headers::{HeaderTimeout, IdleTimeout},
     http::StatusCode,
-    request::RequestBody,
+    request::{RequestBody, RequestConfig},
     response::ResponseWriter,
     router::Router,
-    tls::tls_config,
-    types::{BoltError, ErrorHandler, Handler, Method, Middleware, Mode},
+    types::{
+        BoltError, ErrorHandler, FormDataConfig, Handler, HandlerFuture, Http2Settings, Method,
+        Middleware, MiddlewareOutcome, Mode, OverloadPolicy, TimeoutKind, Timeouts, TlsInfo,
+    },
 };
 
+mod body;
 pub mod client;
 mod error;
+mod file;
 mod group;
 mod headers;
 pub mod http;
 pub mod macros;
+pub mod middleware;
+mod phase;
 pub mod request;
 pub mod response;
 mod router;
+mod sse;
+#[cfg(feature = "templates")]
+mod templates;
+#[cfg(feature = "testing")]
+pub mod testing;
 mod tls;
 pub mod types;
+mod util;
 pub use async_trait;
 pub use bolt_web_macro::main;
+pub use error::ProblemJson;
 pub use paste;
+pub use tls::{ClientAuth, TlsHandle, TlsOptions, TlsVersion};
 pub use tokio;
+pub use util::{blocking, retry};
+
+static NEXT_CONNECTION: AtomicU64 = AtomicU64::new(1);
+static NEXT_REQUEST: AtomicU64 = AtomicU64::new(1);
+
+const MAX_REJECTING: usize = 32;
+const MAX_WAITING: usize = 1024;
 
 trait Io: AsyncRead + AsyncWrite + Unpin {}
 impl<T: AsyncRead + AsyncWrite + Unpin> Io for T {}
@@ -57,10 +90,19 @@ pub struct App {
     router: Router,
     error_handler: Arc<dyn ErrorHandler>,
     client: Client,
-    timeout: u64,
+    timeouts: Timeouts,
+    http2: Http2Settings,
     connection_limit: u64,
-    read_timeout: u64,
+    overload: OverloadPolicy,
     header_limit: usize,
+    allow_trace: bool,
+    strict_slash: bool,
+    banner: bool,
+    shutdown_timeout: Duration,
+    tls: TlsHandle,
+    request_config: RequestConfig,
+    #[cfg(feature = "templates")]
+    templates: Option<Arc<templates::Templates>>,
 }
 
 #[allow(unused_variables)]
@@ -71,29 +113,112 @@ impl App {
             router: Router::new(),
             error_handler: Arc::new(DefaultErrorHandler),
             client: Client::new(),
-            timeout: 30,
+            timeouts: Timeouts::default(),
+            http2: Http2Settings::default(),
             connection_limit: 100,
-            read_timeout: 10,
+            overload: OverloadPolicy::default(),
             header_limit: 32 * 1024,
+            allow_trace: false,
+            strict_slash: false,
+            banner: true,
+            shutdown_timeout: Duration::from_secs(30),
+            tls: TlsHandle::default(),
+            request_config: RequestConfig::default(),
+            #[cfg(feature = "templates")]
+            templates: None,
         }
     }
 
+    pub fn set_timeouts(&mut self, timeouts: Timeouts) {
+        self.timeouts = timeouts;
+        self.request_config.body_read_timeout = timeouts.body_read;
+    }
+
+    pub fn set_http2_settings(&mut self, settings: Http2Settings) {
+        self.http2 = settings;
+    }
+
     pub fn set_timeout(&mut self, seconds: u64) {
-        self.timeout = seconds;
+        self.timeouts.handler = Duration::from_secs(seconds);
     }
 
     pub fn set_connection_limit(&mut self, limit: u64) {
         self.connection_limit = limit;
     }
 
+    pub fn set_overload_policy(&mut self, policy: OverloadPolicy) {
+        self.overload = policy;
+    }
+
     pub fn set_read_timeout(&mut self, seconds: u64) {
-        self.read_timeout = seconds;
+        self.timeouts.header_read = Duration::from_secs(seconds);
     }
 
     pub fn set_header_limit(&mut self, bytes: usize) {
         self.header_limit = bytes;
     }
 
+    pub fn set_allow_trace(&mut self, allow: bool) {
+        self.allow_trace = allow;
+    }
+
+    pub fn set_strict_slash(&mut self, strict: bool) {
+        self.strict_slash = strict;
+    }
+
+    pub fn set_banner(&mut self, banner: bool) {
+        self.banner = banner;
+    }
+
+    pub fn set_shutdown_timeout(&mut self, timeout: Duration) {
+        self.shutdown_timeout = timeout;
+    }
+
+    pub fn trust_proxy(&mut self, addr: IpAddr) {
+        self.request_config.trusted_proxies.push(addr);
+    }
+
+    pub fn set_public_url(&mut self, url: &str) -> Result<(), BoltError> {
+        let url = url::Url::parse(url)?;
+        if url.cannot_be_a_base() {
+            return Err(format!("{} can't be used as a base URL", url).into());
+        }
+
+        self.request_config.public_url = Some(url);
+        Ok(())
+    }
+
+    pub fn set_body_limit(&mut self, bytes: Option<usize>) {
+        self.request_config.body_limit = bytes;
+    }
+
+    pub fn set_content_type_limit(&mut self, media_type: &str, bytes: usize) {
+        self.request_config
+            .body_limits
+            .insert(media_type.to_ascii_lowercase(), bytes);
+    }
+
+    pub fn set_cookie_key(&mut self, secret: &[u8]) -> Result<(), BoltError> {
+        if secret.len() < 32 {
+            return Err("Cookie key must be at least 32 bytes".into());
+        }
+
+        self.request_config.cookie_key = Some(Key::derive_from(secret));
+        Ok(())
+    }
+
+    pub fn set_form_data_config(&mut self, config: FormDataConfig) {
+        self.request_config.form = config;
+    }
+
+    pub fn set_upload_permissions(&mut self, mode: u32) {
+        self.request_config.upload_mode = mode;
+    }
+
+    pub fn set_upload_orphan_ttl(&mut self, ttl: Duration) {
+        self.request_config.upload_orphan_ttl = ttl;
+    }
+
     fn add_route<H>(&mut self, method: Method, path: &str, handler: H)
     where
         H: Handler + 'static,
@@ -136,13 +261,113 @@ impl App {
         self.add_route(Method::DELETE, path, handler);
     }
 
+    pub fn head<H>(&mut self, path: &str, handler: H)
+    where
+        H: Handler + 'static,
+    {
+        self.add_route(Method::HEAD, path, handler);
+    }
+
+    pub fn options<H>(&mut self, path: &str, handler: H)
+    where
+        H: Handler + 'static,
+    {
+        self.add_route(Method::OPTIONS, path, handler);
+    }
+
+    pub fn get_fn<F>(&mut self, path: &str, handler: F)
+    where
+        F: for<'r> Fn(&'r mut RequestBody, &'r mut ResponseWriter) -> HandlerFuture<'r>
+            + Send
+            + Sync
+            + 'static,
+    {
+        self.add_route(Method::GET, path, handler);
+    }
+
+    pub fn post_fn<F>(&mut self, path: &str, handler: F)
+    where
+        F: for<'r> Fn(&'r mut RequestBody, &'r mut ResponseWriter) -> HandlerFuture<'r>
+            + Send
+            + Sync
+            + 'static,
+    {
+        self.add_route(Method::POST, path, handler);
+    }
+
+    pub fn put_fn<F>(&mut self, path: &str, handler: F)
+    where
+        F: for<'r> Fn(&'r mut RequestBody, &'r mut ResponseWriter) -> HandlerFuture<'r>
+            + Send
+            + Sync
+            + 'static,
+    {
+        self.add_route(Method::PUT, path, handler);
+    }
+
+    pub fn patch_fn<F>(&mut self, path: &str, handler: F)
+    where
+        F: for<'r> Fn(&'r mut RequestBody, &'r mut ResponseWriter) -> HandlerFuture<'r>
+            + Send
+            + Sync
+            + 'static,
+    {
+        self.add_route(Method::PATCH, path, handler);
+    }
+
+    pub fn delete_fn<F>(&mut self, path: &str, handler: F)
+    where
+        F: for<'r> Fn(&'r mut RequestBody, &'r mut ResponseWriter) -> HandlerFuture<'r>
+            + Send
+            + Sync
+            + 'static,
+    {
+        self.add_route(Method::DELETE, path, handler);
+    }
+
+    pub fn static_dir(&mut self, url_prefix: &str, fs_root: &str) {
+        self.static_dir_with(url_prefix, fs_root, FileOptions::default());
+    }
+
+    pub fn static_dir_with(&mut self, url_prefix: &str, fs_root: &str, opts: FileOptions) {
+        let route = format!("{}/*path", url_prefix.trim_end_matches('/'));
+
+        self.router.insert(
+            &route,
+            Method::GET,
+            file::StaticDir::new(fs_root, opts.clone()),
+        );
+        self.router
+            .insert(&route, Method::HEAD, file::StaticDir::new(fs_root, opts));
+    }
+
     pub fn group<'a>(&'a mut self, path: &str) -> Group<'a> {
         Group {
             prefix: path.to_string(),
+            host: None,
+            app: self,
+        }
+    }
+
+    pub fn host<'a>(&'a mut self, hostname: &str) -> Group<'a> {
+        Group {
+            prefix: String::new(),
+            host: Some(hostname.to_string()),
             app: self,
         }
     }
 
+    pub fn routes(&self) -> Vec<(Method, String)> {
+        self.router.routes()
+    }
+
+    fn router_for(&mut self, host: Option<&str>) -> &mut Router {
+        match host {
+            Some(h) => self.router.host_mut(h),
+            None => &mut self.router,
+        }
+    }
+
     pub fn middleware<M>(&mut self, path: &str, method: Option<Method>, middleware_fn: M)
     where
         M: Middleware + 'static,
@@ -153,22 +378,31 @@ impl App {
         match method {
             Some(m) => self.router.insert_middleware(&full_path, m, mw),
             None => {
-                for m in [
-                    Method::GET,
-                    Method::POST,
-                    Method::PUT,
-                    Method::PATCH,
-                    Method::DELETE,
-                    Method::OPTIONS,
-                    Method::HEAD,
-                    Method::TRACE,
-                ] {
+                for m in Method::ALL {
                     self.router.insert_middleware(&full_path, m, mw.clone());
                 }
             }
         }
     }
 
+    pub fn tls_handle(&self) -> TlsHandle {
+        self.tls.clone()
+    }
+
+    pub fn set_tls_options(&mut self, options: TlsOptions) -> Result<(), BoltError> {
+        Ok(self.tls.set_options(options)?)
+    }
+
+    pub fn reload_tls(&self, cert: &str, key: &str) -> Result<(), BoltError> {
+        Ok(self.tls.reload(cert, key)?)
+    }
+
+    #[cfg(feature = "templates")]
+    pub fn set_template_dir(&mut self, dir: &str) -> Result<(), BoltError> {
+        self.templates = Some(Arc::new(templates::Templates::new(dir)?));
+        Ok(())
+    }
+
     pub fn set_error_handler<E>(&mut self, handler: E)
     where
         E: ErrorHandler + 'static,
@@ -177,22 +411,95 @@ impl App {
     }
 
     pub async fn run(&self, addr: &str, mode: Mode) -> Result<(), BoltError> {
-        println!("⚡ A high performance & minimalist web framework in rust.");
-        println!(
-            r#"
-    __          ____
-   / /_  ____  / / /_
-  / __ \/ __ \/ / __/
- / /_/ / /_/ / / /_  
-/_.___/\____/_/\__/  v0.2.0
-"#
-        );
-
-        println!(">> Server running on http://{}", addr);
+        self.run_with_shutdown(addr, mode, tokio::signal::ctrl_c().map(|_| ()))
+            .await
+    }
 
-        let addr: SocketAddr = addr.parse().unwrap();
+    pub async fn run_with_shutdown<F>(
+        &self,
+        addr: &str,
+        mode: Mode,
+        shutdown: F,
+    ) -> Result<(), BoltError>
+    where
+        F: Future<Output = ()> + Send + 'static,
+    {
+        self.banner();
 
+        let addr: SocketAddr = addr.parse()?;
         let listener = TcpListener::bind(addr).await?;
+
+        info!("Server running on http://{}", listener.local_addr()?);
+
+        self.run_listener_with_shutdown(listener, mode, shutdown)
+            .await
+    }
+
+    pub async fn run_multi(&self, addrs: &[&str], mode: Mode) -> Result<(), BoltError> {
+        self.run_multi_with_shutdown(addrs, mode, tokio::signal::ctrl_c().map(|_| ()))
+            .await
+    }
+
+    pub async fn run_multi_with_shutdown<F>(
+        &self,
+        addrs: &[&str],
+        mode: Mode,
+        shutdown: F,
+    ) -> Result<(), BoltError>
+    where
+        F: Future<Output = ()> + Send + 'static,
+    {
+        let mut listeners = Vec::with_capacity(addrs.len());
+
+        for addr in addrs {
+            let listener = async {
+                let addr: SocketAddr = addr.parse()?;
+                Ok::<_, BoltError>(TcpListener::bind(addr).await?)
+            };
+
+            match listener.await {
+                Ok(listener) => {
+                    info!("Server running on http://{}", listener.local_addr()?);
+                    listeners.push(listener);
+                }
+                Err(e) => return Err(format!("Failed to bind {}: {}", addr, e).into()),
+            }
+        }
+
+        let router = Arc::new(self.router.clone());
+        let active = Arc::new(Semaphore::new(self.connection_limit as usize));
+        let shutdown = Box::pin(shutdown).shared();
+
+        let loops = listeners.into_iter().map(|listener| {
+            self.server_loop(
+                router.clone(),
+                self.error_handler.clone(),
+                listener,
+                mode,
+                None,
+                Box::pin(shutdown.clone()),
+                active.clone(),
+            )
+        });
+
+        futures_util::future::try_join_all(loops).await?;
+        Ok(())
+    }
+
+    pub async fn run_listener(&self, listener: TcpListener, mode: Mode) -> Result<(), BoltError> {
+        self.run_listener_with_shutdown(listener, mode, tokio::signal::ctrl_c().map(|_| ()))
+            .await
+    }
+
+    pub async fn run_listener_with_shutdown<F>(
+        &self,
+        listener: TcpListener,
+        mode: Mode,
+        shutdown: F,
+    ) -> Result<(), BoltError>
+    where
+        F: Future<Output = ()> + Send + 'static,
+    {
         let router = Arc::new(self.router.clone());
         let error_handler = self.error_handler.clone();
         let active = Arc::new(Semaphore::new(self.connection_limit as usize));
@@ -203,9 +510,7 @@ impl App {
             listener,
             mode,
             None,
-            self.timeout,
-            self.read_timeout,
-            Box::pin(tokio::signal::ctrl_c().map(|_| ())),
+            Box::pin(shutdown),
             active,
         )
         .await
@@ -217,36 +522,42 @@ impl App {
         mode: Mode,
         tls: Option<(&str, &str)>,
     ) -> Result<(), BoltError> {
-        println!("⚡ A high performance & minimalist web framework in rust.");
-        println!(
-            "{}",
-            r#"
-    __          ____
-   / /_  ____  / / /_
-  / __ \/ __ \/ / __/
- / /_/ / /_/ / / /_  
-/_.___/\____/_/\__/  v0.2.0
-"#
-        );
+        self.run_tls_with_shutdown(addr, mode, tls, tokio::signal::ctrl_c().map(|_| ()))
+            .await
+    }
 
-        let addr: SocketAddr = addr.parse().unwrap();
+    pub async fn run_tls_with_shutdown<F>(
+        &self,
+        addr: &str,
+        mode: Mode,
+        tls: Option<(&str, &str)>,
+        shutdown: F,
+    ) -> Result<(), BoltError>
+    where
+        F: Future<Output = ()> + Send + 'static,
+    {
+        self.banner();
+
+        let addr: SocketAddr = addr.parse()?;
         let listener = TcpListener::bind(addr).await?;
 
-        let tls_acceptor: Option<Arc<TlsAcceptor>> = if let Some((cert, key)) = tls {
-            let cfg = tls_config(cert, key)?;
-            Some(Arc::new(TlsAcceptor::from(cfg)))
-        } else {
-            None
+        let tls_handle = match tls {
+            Some((cert, key)) => {
+                self.tls.set_alpn(mode)?;
+                self.tls.reload(cert, key)?;
+                Some(self.tls.clone())
+            }
+            None => None,
         };
 
-        println!(
-            ">> Server running on {}://{}",
-            if tls_acceptor.is_some() {
+        info!(
+            "Server running on {}://{}",
+            if tls_handle.is_some() {
                 "https"
             } else {
                 "http"
             },
-            addr
+            listener.local_addr()?
         );
 
         let router: Arc<Router> = Arc::new(self.router.clone());
@@ -258,201 +569,462 @@ impl App {
             error_handler,
             listener,
             mode,
-            tls_acceptor,
-            self.timeout,
-            self.read_timeout,
-            Box::pin(tokio::signal::ctrl_c().map(|_| ())),
+            tls_handle,
+            Box::pin(shutdown),
             active,
         )
         .await
     }
 
+    fn banner(&self) {
+        if !self.banner {
+            return;
+        }
+
+        println!("⚡ A high performance & minimalist web framework in rust.");
+        println!(
+            r#"
+    __          ____
+   / /_  ____  / / /_
+  / __ \/ __ \/ / __/
+ / /_/ / /_/ / / /_  
+/_.___/\____/_/\__/  v0.2.0
+"#
+        );
+    }
+
     async fn server_loop(
         &self,
         router: Arc<Router>,
         error_handler: Arc<dyn ErrorHandler>,
         listener: TcpListener,
         mode: Mode,
-        tls_acceptor: Option<Arc<TlsAcceptor>>,
-        timeout: u64,
-        read_timeout: u64,
+        tls_handle: Option<TlsHandle>,
         mut shutdown: Pin<Box<dyn Future<Output = ()> + Send>>,
         active: Arc<Semaphore>,
     ) -> Result<(), BoltError> {
+        let config = Arc::new(self.request_config.clone());
+        #[cfg(feature = "templates")]
+        let templates = self.templates.clone();
+        let timeouts = self.timeouts;
+        let http2_settings = self.http2;
+        let header_limit = self.header_limit.max(8192);
+        let allow_trace = self.allow_trace;
+        let strict_slash = self.strict_slash;
+        let overload = self.overload;
+
+        let ttl = config.upload_orphan_ttl;
+        let dir = config.form.temp_dir();
+        let _ = tokio::task::spawn_blocking(move || request::sweep_uploads(&dir, ttl)).await;
+
+        let (drain_tx, drain_rx) = watch::channel(());
+        let rejecting = Arc::new(Semaphore::new(MAX_REJECTING));
+        let mut waiting = FuturesUnordered::new();
+
         loop {
-            tokio::select! {
+            let (stream, remote_addr, permit) = tokio::select! {
                 _ = &mut shutdown => {
-                    println!(">> Shutdown signal received. Stopping server...");
+                    info!("Shutdown signal received, stopping server");
                     break;
                 }
 
+                Some(waited) = waiting.next(), if !waiting.is_empty() => waited,
+
                 accept_res = listener.accept() => {
                     let (stream, remote_addr) = match accept_res {
                         Ok(v) => v,
                         Err(e) => {
-                            eprintln!("Accept error: {}", e);
-                            continue;
-                        }
-                    };
-
-                    let permit = match active.clone().try_acquire_owned() {
-                        Ok(p) => p,
-                        Err(_) => {
-                            eprintln!("Connection limit reached — dropping client");
+                            tracing::error!("Accept error: {}", e);
                             continue;
                         }
                     };
 
-                    let io: Box<dyn Io + Send> = if let Some(ref acceptor) = tls_acceptor {
-                        match acceptor.accept(stream).await {
-                            Ok(c) => Box::new(c),
-                            Err(e) => {
-                                eprintln!("TLS error: {}", e);
+                    match active.clone().try_acquire_owned() {
+                        Ok(p) => (stream, remote_addr, Some(p)),
+                        Err(_) => match overload {
+                            OverloadPolicy::Wait(bound) if waiting.len() < MAX_WAITING => {
+                                let active = active.clone();
+                                waiting.push(async move {
+                                    let permit = tokio::time::timeout(bound, active.acquire_owned())
+                                        .await
+                                        .ok()
+                                        .and_then(Result::ok);
+                                    (stream, remote_addr, permit)
+                                });
                                 continue;
                             }
-                        }
-                    } else {
-                        Box::new(stream)
-                    };
+                            _ => (stream, remote_addr, None),
+                        },
+                    }
+                }
+            };
 
-                    let limited = LimitReader::new(io, self.header_limit);
-                    let io = TokioIo::new(limited);
-
-                    let router = router.clone();
-                    let error_handler = error_handler.clone();
-
-                    let service = service_fn(move |req: Request<Incoming>| {
-                        let router = router.clone();
-                        let error_handler = error_handler.clone();
-                        let remote_addr = remote_addr.clone();
-                        let timeout = timeout;
-
-                        async move {
-                            let handler_future = tokio::time::timeout(
-                                Duration::from_secs(timeout),
-                                async {
-                                    let inner = AssertUnwindSafe(async move {
-                                        let mut req_body = RequestBody::new(req, remote_addr);
-                                        let mut res_body = ResponseWriter::new();
-
-                                        let method = match *req_body.method() {
-                                            hyper::Method::GET => Method::GET,
-                                            hyper::Method::POST => Method::POST,
-                                            hyper::Method::PUT => Method::PUT,
-                                            hyper::Method::PATCH => Method::PATCH,
-                                            hyper::Method::DELETE => Method::DELETE,
-                                            hyper::Method::OPTIONS => Method::OPTIONS,
-                                            hyper::Method::HEAD => Method::HEAD,
-                                            hyper::Method::TRACE => Method::TRACE,
-                                            _ => {
-                                                res_body.status(StatusCode::MethodNotAllowed)
-                                                        .send("Method Not Allowed");
-                                                return res_body;
-                                            }
-                                        };
+            let Some(permit) = permit else {
+                match rejecting.clone().try_acquire_owned() {
+                    Ok(slot) if overload != OverloadPolicy::Drop => {
+                        warn!(remote = %remote_addr, "Connection limit reached, rejecting client");
+                        let acceptor = tls_handle.as_ref().and_then(|h| h.acceptor());
+                        tokio::spawn(reject(stream, acceptor, timeouts.header_read, slot));
+                    }
+                    _ => {
+                        warn!(remote = %remote_addr, "Connection limit reached, dropping client");
+                    }
+                }
+                continue;
+            };
 
-                                        let path = req_body.path().to_string();
-                                        for mw in router.collect_middleware(&path, method) {
-                                            mw.run(&mut req_body, &mut res_body).await;
-                                            if res_body.has_error() { break; }
-                                        }
-
-                                        if !res_body.has_error() {
-                                            if let Some((handler, params)) = router.find(&path, method) {
-                                                req_body.set_params(params);
-                                                handler.run(&mut req_body, &mut res_body).await;
-                                            } else {
-                                                res_body.error(
-                                                    StatusCode::NotFound,
-                                                    &format!("Not Found {} {}", req_body.method(), path),
-                                                );
-                                            }
-                                        }
-
-                                        if res_body.has_error() {
-                                            let msg = res_body.body.clone();
-                                            error_handler.run(msg, &mut res_body).await;
-                                        }
-
-                                        req_body.cleanup().await;
-                                        res_body
-                                    })
-                                    .catch_unwind()
-                                    .await;
-
-                                    match inner {
-                                        Ok(r) => r,
-                                        Err(_) => {
-                                            let mut res = ResponseWriter::new();
-                                            res.error(StatusCode::InternalServerError, "Internal Server Error");
-                                            res
-                                        }
-                                    }
-                                }
-                            ).await;
-
-                            let res_body = match handler_future {
-                                Ok(res) => res,
-                                Err(_) => {
-                                    let mut res = ResponseWriter::new();
-                                    res.error(StatusCode::RequestTimeout, "Request Timeout");
-                                    res
+            let (io, tls_info): (Box<dyn Io + Send>, Option<Arc<TlsInfo>>) = if let Some(acceptor) =
+                tls_handle.as_ref().and_then(|h| h.acceptor())
+            {
+                match tokio::time::timeout(timeouts.header_read, acceptor.accept(stream)).await {
+                    Ok(Ok(c)) => {
+                        let (_, conn) = c.get_ref();
+                        let info = TlsInfo {
+                            sni: conn.server_name().map(|s| s.to_string()),
+                            alpn: conn
+                                .alpn_protocol()
+                                .map(|p| String::from_utf8_lossy(p).into_owned()),
+                            version: conn.protocol_version().map(|v| format!("{:?}", v)),
+                            client_cert: conn
+                                .peer_certificates()
+                                .and_then(|c| c.first())
+                                .map(|c| tls::client_cert(c)),
+                        };
+                        (Box::new(c), Some(Arc::new(info)))
+                    }
+                    Ok(Err(e)) => {
+                        warn!(remote = %remote_addr, "TLS error: {}", e);
+                        continue;
+                    }
+                    Err(_) => {
+                        warn!(remote = %remote_addr, "TLS handshake timeout");
+                        continue;
+                    }
+                }
+            } else {
+                (Box::new(stream), None)
+            };
+
+            let http2 = match mode {
+                Mode::Http1 => Some(false),
+                Mode::Http2 => Some(true),
+                Mode::Auto => tls_info.as_ref().map(|t| t.alpn.as_deref() == Some("h2")),
+            };
+
+            let reading = Arc::new(AtomicBool::new(true));
+            let guarded = HeaderTimeout::new(io, timeouts.header_read, reading.clone());
+            let io = TokioIo::new(IdleTimeout::new(guarded, timeouts.idle_keepalive));
+
+            let router = router.clone();
+            let error_handler = error_handler.clone();
+            let config = config.clone();
+            #[cfg(feature = "templates")]
+            let templates = templates.clone();
+
+            let conn_span = info_span!(
+                "connection",
+                id = NEXT_CONNECTION.fetch_add(1, Ordering::Relaxed),
+                remote = %remote_addr,
+            );
+
+            let service = service_fn(move |req: Request<Incoming>| {
+                reading.store(false, Ordering::Release);
+                let reading = reading.clone();
+                let router = router.clone();
+                let error_handler = error_handler.clone();
+                let tls_info = tls_info.clone();
+                let config = config.clone();
+                #[cfg(feature = "templates")]
+                let templates = templates.clone();
+
+                let span = info_span!(
+                    "request",
+                    id = NEXT_REQUEST.fetch_add(1, Ordering::Relaxed),
+                    method = %req.method(),
+                    path = req.uri().path(),
+                    request_id = field::Empty,
+                );
+
+                let head = req.method() == hyper::Method::HEAD;
+                let path = req.uri().path().to_string();
+
+                async move {
+                    let phases = Arc::new(phase::Phases::default());
+                    let handler_error = error_handler.clone();
+
+                    let handler_future = phase::scope(phases.clone(), async {
+                        let inner = AssertUnwindSafe(async move {
+                            let mut req_body = RequestBody::new(req, remote_addr);
+                            req_body.set_tls(tls_info);
+                            let mut res_body = ResponseWriter::new();
+                            res_body.set_cookie_key(config.cookie_key.clone());
+                            req_body.set_config(config);
+                            res_body.set_base_url(req_body.public_url());
+                            #[cfg(feature = "templates")]
+                            res_body.set_templates(templates);
+
+                            let method = match *req_body.method() {
+                                hyper::Method::GET => Method::GET,
+                                hyper::Method::POST => Method::POST,
+                                hyper::Method::PUT => Method::PUT,
+                                hyper::Method::PATCH => Method::PATCH,
+                                hyper::Method::DELETE => Method::DELETE,
+                                hyper::Method::OPTIONS => Method::OPTIONS,
+                                hyper::Method::HEAD => Method::HEAD,
+                                hyper::Method::TRACE => Method::TRACE,
+                                hyper::Method::CONNECT => Method::CONNECT,
+                                _ => {
+                                    res_body
+                                        .status(StatusCode::MethodNotAllowed)
+                                        .send("Method Not Allowed");
+                                    return res_body;
                                 }
                             };
 
-                            Ok::<_, Infallible>(res_body.into_response())
-                        }
-                    });
+                            if method == Method::TRACE && !allow_trace {
+                                res_body.error(StatusCode::MethodNotAllowed, "Method Not Allowed");
+                                let msg = String::from_utf8_lossy(&res_body.body).into_owned();
+                                error_handler.run(msg, &mut res_body).await;
+                                return res_body;
+                            }
 
-                    let permit = permit;
+                            let path = router::normalize(req_body.path());
+                            let scoped = req_body.host().and_then(|h| router.for_host(h));
+                            let routes = scoped.unwrap_or(&router);
+
+                            let mut chain = router.collect_middleware(&path, method);
+                            if let Some(scoped) = scoped {
+                                chain.extend(scoped.collect_middleware(&path, method));
+                            }
+                            for mw in &chain {
+                                match mw.handle(&mut req_body, &mut res_body).await {
+                                    MiddlewareOutcome::Continue => {}
+                                    MiddlewareOutcome::Stop => {
+                                        res_body.complete();
+                                    }
+                                    MiddlewareOutcome::Error(e) => {
+                                        res_body.error(e.status, &e.message);
+                                    }
+                                }
 
-                    match mode {
-                        Mode::Http1 => {
-                            tokio::spawn(async move {
-                                let _permit = permit;
+                                if res_body.has_error() || res_body.is_complete() {
+                                    break;
+                                }
+                            }
 
-                                let result = tokio::time::timeout(
-                                    Duration::from_secs(read_timeout),
-                                    async {
-                                        http1::Builder::new()
-                                            .serve_connection(io, service)
-                                            .await
+                            if !res_body.has_error() && !res_body.is_complete() {
+                                if let Some((handler, params, route)) = routes.find(&path, method) {
+                                    if strict_slash
+                                        && let Some(target) = router::slash_redirect(route, &path)
+                                    {
+                                        let location = match req_body.uri().query() {
+                                            Some(q) => format!("{}?{}", target, q),
+                                            None => target,
+                                        };
+                                        res_body.redirect_permanent(&location);
+                                    } else {
+                                        req_body.set_params(params);
+                                        req_body.set_route(route, routes.methods_for(route));
+                                        handler.run(&mut req_body, &mut res_body).await;
+                                    }
+                                } else {
+                                    let mut allowed = routes.allowed_methods(&path);
+                                    if !allowed.is_empty() && !allowed.contains(&Method::OPTIONS) {
+                                        allowed.push(Method::OPTIONS);
                                     }
-                                ).await;
 
-                                match result {
-                                    Ok(Ok(_)) => {}
-                                    Ok(Err(e)) => eprintln!("Connection error: {}", e),
-                                    Err(_) => eprintln!("Slowloris: read timeout — closing connection"),
+                                    if allowed.is_empty() {
+                                        res_body.error(
+                                            StatusCode::NotFound,
+                                            &format!("Not Found {} {}", req_body.method(), path),
+                                        );
+                                    } else if method == Method::OPTIONS {
+                                        res_body.status(StatusCode::NoContent).allow(&allowed);
+                                    } else {
+                                        res_body.allow(&allowed).error(
+                                            StatusCode::MethodNotAllowed,
+                                            "Method Not Allowed",
+                                        );
+                                    }
                                 }
-                            });
+                            }
+
+                            if res_body.has_error() {
+                                let msg = String::from_utf8_lossy(&res_body.body).into_owned();
+                                error_handler.run(msg, &mut res_body).await;
+                            }
+
+                            for mw in chain.iter().rev() {
+                                mw.after(&mut req_body, &mut res_body).await;
+                            }
+
+                            req_body.cleanup().await;
+                            res_body
+                        })
+                        .catch_unwind()
+                        .await;
+
+                        match inner {
+                            Ok(r) => r,
+                            Err(payload) => {
+                                let message = panic_message(payload.as_ref());
+                                tracing::error!(path = %path, "Handler panicked: {}", message);
+
+                                let mut res = ResponseWriter::new();
+                                res.fallback_error();
+                                res
+                            }
                         }
+                    });
+                    let mut handler_future = std::pin::pin!(handler_future);
 
-                        Mode::Http2 => {
-                            tokio::spawn(async move {
-                                let _permit = permit;
+                    let res_body = tokio::select! {
+                        res = &mut handler_future => res,
+                        _ = tokio::time::sleep(timeouts.handler) => {
+                            let kind = phases.current();
+                            let msg = match kind {
+                                TimeoutKind::Body => "Request Timeout",
+                                TimeoutKind::Upstream | TimeoutKind::Handler => "Gateway Timeout",
+                            };
 
-                                let result = tokio::time::timeout(
-                                    Duration::from_secs(read_timeout),
-                                    async {
-                                        http2::Builder::new(TokioExecutor::new())
-                                            .serve_connection(io, service)
-                                            .await
-                                    }
-                                ).await;
+                            let mut res = ResponseWriter::new();
+                            res.timed_out(kind, msg);
+                            handler_error.run(msg.to_string(), &mut res).await;
+                            res
+                        }
+                    };
 
-                                match result {
-                                    Ok(Ok(_)) => {}
-                                    Ok(Err(e)) => eprintln!("Connection error: {}", e),
-                                    Err(_) => eprintln!("Slowloris: read timeout — closing connection"),
-                                }
-                            });
+                    let mut response = res_body.into_response();
+                    if head {
+                        *response.body_mut() =
+                            Empty::new().map_err(|never| match never {}).boxed_unsync();
+                    }
+
+                    reading.store(true, Ordering::Release);
+                    Ok::<_, Infallible>(response)
+                }
+                .instrument(span)
+            });
+
+            let permit = permit;
+            let draining = drain_rx.clone();
+
+            tokio::spawn(
+                async move {
+                    let _permit = permit;
+
+                    let mut builder = auto::Builder::new(TokioExecutor::new());
+                    builder
+                        .http1()
+                        .timer(TokioTimer::new())
+                        .header_read_timeout(None)
+                        .max_buf_size(header_limit);
+                    builder
+                        .http2()
+                        .timer(TokioTimer::new())
+                        .max_concurrent_streams(http2_settings.max_concurrent_streams)
+                        .initial_stream_window_size(http2_settings.initial_stream_window_size)
+                        .initial_connection_window_size(
+                            http2_settings.initial_connection_window_size,
+                        )
+                        .max_frame_size(http2_settings.max_frame_size)
+                        .max_header_list_size(http2_settings.max_header_list_size)
+                        .max_pending_accept_reset_streams(
+                            http2_settings.max_pending_accept_reset_streams,
+                        )
+                        .keep_alive_interval(http2_settings.keep_alive_interval)
+                        .keep_alive_timeout(http2_settings.keep_alive_timeout);
+
+                    let builder = match http2 {
+                        Some(true) => builder.http2_only(),
+                        Some(false) => builder.http1_only(),
+                        None => builder,
+                    };
+
+                    let conn = builder.serve_connection(io, service);
+                    let mut conn = std::pin::pin!(conn);
+                    let mut draining = draining.clone();
+
+                    let serve = async {
+                        tokio::select! {
+                            res = conn.as_mut() => res,
+                            _ = draining.changed() => {
+                                conn.as_mut().graceful_shutdown();
+                                conn.as_mut().await
+                            }
                         }
+                    };
+
+                    match tokio::time::timeout(timeouts.total.unwrap_or(Duration::MAX), serve).await
+                    {
+                        Ok(Ok(_)) => {}
+                        Ok(Err(e)) => debug!("Connection error: {}", e),
+                        Err(_) => warn!("Total connection timeout, closing connection"),
                     }
                 }
-            }
+                .instrument(conn_span),
+            );
+        }
+
+        drop(listener);
+        let _ = drain_tx.send(());
+
+        let connections = u32::try_from(self.connection_limit).unwrap_or(u32::MAX);
+
+        if tokio::time::timeout(self.shutdown_timeout, active.acquire_many(connections))
+            .await
+            .is_err()
+        {
+            warn!(
+                "Shutdown timeout, {} connection(s) still open",
+                connections as usize - active.available_permits()
+            );
         }
 
         Ok(())
     }
 }
+
+fn panic_message(payload: &(dyn Any + Send)) -> &str {
+    if let Some(message) = payload.downcast_ref::<&str>() {
+        message
+    } else if let Some(message) = payload.downcast_ref::<String>() {
+        message
+    } else {
+        "unknown panic payload"
+    }
+}
+
+async fn reject(
+    stream: TcpStream,
+    acceptor: Option<TlsAcceptor>,
+    timeout: Duration,
+    _slot: OwnedSemaphorePermit,
+) {
+    let serve = async move {
+        let io: Box<dyn Io + Send> = match acceptor {
+            Some(acceptor) => Box::new(acceptor.accept(stream).await?),
+            None => Box::new(stream),
+        };
+
+        let service = service_fn(|_req: Request<Incoming>| async {
+            let mut res = ResponseWriter::new();
+            res.error(StatusCode::ServiceUnavailable, "Service Unavailable");
+            res.set_header("Content-Type", "text/plain; charset=utf-8");
+            res.set_header("Retry-After", "1");
+            Ok::<_, Infallible>(res.into_response())
+        });
+
+        let mut builder = auto::Builder::new(TokioExecutor::new());
+        builder.http1().keep_alive(false);
+
+        builder
+            .serve_connection(TokioIo::new(io), service)
+            .await
+            .map_err(io::Error::other)
+    };
+
+    if let Ok(Err(e)) = tokio::time::timeout(timeout, serve).await {
+        debug!("Rejected connection error: {}", e);
+    }
+}
@@ -11,7 +11,8 @@ macro_rules! handler {
                     req: &mut $crate::request::RequestBody,
                     res: &mut $crate::response::ResponseWriter
                 ) {
-                    $fn_name(req, res).await;
+                    let result = $fn_name(req, res).await;
+                    $crate::types::HandlerResult::apply(result, res);
                 }
             }
             #[allow(non_upper_case_globals)]
@@ -29,8 +30,9 @@ macro_rules! middleware {
 
             #[async_trait::async_trait]
             impl $crate::types::Middleware for [<$fn_name:camel Middleware>] {
-                async fn run(&self, req: &mut $crate::request::RequestBody, res: &mut $crate::response::ResponseWriter) {
+                async fn handle(&self, req: &mut $crate::request::RequestBody, res: &mut $crate::response::ResponseWriter) -> $crate::types::MiddlewareOutcome {
                     $fn_name(req, res).await;
+                    $crate::types::MiddlewareOutcome::Continue
                 }
             }
 
@@ -0,0 +1,122 @@
+use async_trait::async_trait;
+use hyper::header::{AUTHORIZATION, HeaderValue, WWW_AUTHENTICATE};
+use jsonwebtoken::{Algorithm, DecodingKey, Validation, errors::ErrorKind};
+use serde::de::DeserializeOwned;
+use serde_json::Value;
+
+use crate::{
+    http::{HttpError, StatusCode},
+    request::RequestBody,
+    response::ResponseWriter,
+    types::{BoltError, Middleware, MiddlewareOutcome},
+};
+
+#[derive(Debug, Clone)]
+pub struct Claims(pub Value);
+
+impl Claims {
+    pub fn get<T: DeserializeOwned>(&self, key: &str) -> Option<T> {
+        serde_json::from_value(self.0.get(key)?.clone()).ok()
+    }
+
+    pub fn decode<T: DeserializeOwned>(&self) -> Result<T, serde_json::Error> {
+        serde_json::from_value(self.0.clone())
+    }
+}
+
+#[derive(Clone)]
+pub struct Auth {
+    key: DecodingKey,
+    validation: Validation,
+}
+
+impl Auth {
+    pub fn hs256(secret: &[u8]) -> Self {
+        Self::with_key(DecodingKey::from_secret(secret), Algorithm::HS256)
+    }
+
+    pub fn rs256(public_key_pem: &[u8]) -> Result<Self, BoltError> {
+        let key = DecodingKey::from_rsa_pem(public_key_pem)?;
+        Ok(Self::with_key(key, Algorithm::RS256))
+    }
+
+    fn with_key(key: DecodingKey, algorithm: Algorithm) -> Self {
+        let mut validation = Validation::new(algorithm);
+        validation.validate_aud = false;
+
+        Self { key, validation }
+    }
+
+    pub fn audience(mut self, audience: &[&str]) -> Self {
+        self.validation.set_audience(audience);
+        self.validation.validate_aud = true;
+        self.require("aud");
+        self
+    }
+
+    pub fn issuer(mut self, issuer: &[&str]) -> Self {
+        self.validation.set_issuer(issuer);
+        self.require("iss");
+        self
+    }
+
+    fn require(&mut self, claim: &str) {
+        self.validation
+            .required_spec_claims
+            .insert(claim.to_string());
+    }
+
+    pub fn leeway(mut self, secs: u64) -> Self {
+        self.validation.leeway = secs;
+        self
+    }
+
+    fn verify(&self, req: &RequestBody) -> Result<Claims, (&'static str, &'static str)> {
+        let header = req
+            .headers()
+            .get(AUTHORIZATION)
+            .ok_or(("invalid_request", "Missing bearer token"))?;
+
+        let token = header
+            .to_str()
+            .ok()
+            .and_then(|v| v.trim().split_once(' '))
+            .filter(|(scheme, _)| scheme.eq_ignore_ascii_case("bearer"))
+            .map(|(_, token)| token.trim())
+            .filter(|t| !t.is_empty())
+            .ok_or(("invalid_request", "Malformed authorization header"))?;
+
+        jsonwebtoken::decode::<Value>(token, &self.key, &self.validation)
+            .map(|data| Claims(data.claims))
+            .map_err(|e| match e.kind() {
+                ErrorKind::ExpiredSignature => ("invalid_token", "Token expired"),
+                ErrorKind::InvalidAudience => ("invalid_token", "Invalid token audience"),
+                ErrorKind::InvalidIssuer => ("invalid_token", "Invalid token issuer"),
+                ErrorKind::ImmatureSignature => ("invalid_token", "Token not yet valid"),
+                ErrorKind::MissingRequiredClaim(_) => {
+                    ("invalid_token", "Token missing required claim")
+                }
+                _ => ("invalid_token", "Invalid token"),
+            })
+    }
+}
+
+#[async_trait]
+impl Middleware for Auth {
+    async fn handle(&self, req: &mut RequestBody, res: &mut ResponseWriter) -> MiddlewareOutcome {
+        match self.verify(req) {
+            Ok(claims) => {
+                req.set_ext(claims);
+                MiddlewareOutcome::Continue
+            }
+            Err((code, message)) => {
+                let challenge = format!("Bearer error=\"{}\"", code);
+                if let Ok(value) = HeaderValue::from_str(&challenge) {
+                    res.headers.insert(WWW_AUTHENTICATE, value);
+                }
+
+                MiddlewareOutcome::Error(HttpError::new(StatusCode::Unauthorized, message))
+            }
+        }
+    }
+}
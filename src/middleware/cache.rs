@@ -0,0 +1,207 @@
+use std::{
+    collections::HashMap,
+    sync::{Arc, Mutex},
+    time::{Duration, Instant},
+};
+
+use async_trait::async_trait;
+use bytes::Bytes;
+use hyper::HeaderMap;
+
+use crate::{
+    http::StatusCode,
+    request::{RequestBody, parse_quality_list},
+    response::ResponseWriter,
+    types::{Middleware, MiddlewareOutcome},
+};
+
+#[derive(Clone)]
+struct Entry {
+    expires: Instant,
+    status: StatusCode,
+    headers: HeaderMap,
+    body: Bytes,
+}
+
+/// Header names already on the response when a miss reached the cache. Those were set by
+/// outer middleware for this request only, so they are left out of the stored entry.
+struct Outer(Vec<hyper::header::HeaderName>);
+
+#[derive(Clone)]
+pub struct Cache {
+    ttl: Duration,
+    vary: Vec<String>,
+    entries: Arc<Mutex<HashMap<String, Entry>>>,
+}
+
+impl Cache {
+    pub fn new(ttl: Duration) -> Self {
+        Self {
+            ttl,
+            vary: Vec::new(),
+            entries: Arc::new(Mutex::new(HashMap::new())),
+        }
+    }
+
+    pub fn vary(mut self, header: &str) -> Self {
+        self.vary.push(header.to_ascii_lowercase());
+        self
+    }
+
+    fn key(&self, req: &RequestBody) -> Option<String> {
+        if req.method() != hyper::Method::GET && req.method() != hyper::Method::HEAD {
+            return None;
+        }
+
+        let mut key = format!("{} {}", req.method(), req.uri());
+
+        for name in self.varied() {
+            let value = req
+                .headers()
+                .get(name)
+                .and_then(|v| v.to_str().ok())
+                .unwrap_or_default();
+
+            key.push_str(&format!("\n{}:{}", name, value));
+        }
+
+        Some(key)
+    }
+
+    fn varied(&self) -> impl Iterator<Item = &str> {
+        std::iter::once("accept-encoding").chain(
+            self.vary
+                .iter()
+                .map(String::as_str)
+                .filter(|name| *name != "accept-encoding"),
+        )
+    }
+
+    /// A response that varies on a header the key doesn't include would be replayed to
+    /// clients it wasn't negotiated for.
+    fn covers_vary(&self, res: &ResponseWriter) -> bool {
+        res.headers
+            .get_all(hyper::header::VARY)
+            .iter()
+            .filter_map(|v| v.to_str().ok())
+            .flat_map(|v| v.split(','))
+            .map(|name| name.trim().to_ascii_lowercase())
+            .filter(|name| !name.is_empty())
+            .all(|name| self.varied().any(|varied| varied == name))
+    }
+}
+
+fn storable(req: &RequestBody, res: &ResponseWriter) -> bool {
+    if res.headers.contains_key(hyper::header::SET_COOKIE) {
+        return false;
+    }
+
+    let private = res
+        .headers
+        .get_all(hyper::header::CACHE_CONTROL)
+        .iter()
+        .filter_map(|v| v.to_str().ok())
+        .flat_map(|v| v.split(','))
+        .map(|d| d.trim().to_ascii_lowercase())
+        .any(|d| d == "no-store" || d == "private" || d.starts_with("private="));
+
+    if private {
+        return false;
+    }
+
+    let Some(encoding) = res.headers.get(hyper::header::CONTENT_ENCODING) else {
+        return true;
+    };
+
+    let Ok(encoding) = encoding.to_str() else {
+        return false;
+    };
+
+    let encoding = encoding.trim().to_ascii_lowercase();
+    if encoding == "identity" {
+        return true;
+    }
+
+    let accepted = req
+        .headers()
+        .get(hyper::header::ACCEPT_ENCODING)
+        .and_then(|v| v.to_str().ok())
+        .map(parse_quality_list)
+        .unwrap_or_default();
+
+    accepted
+        .iter()
+        .any(|(name, _)| *name == encoding || name == "*")
+}
+
+#[async_trait]
+impl Middleware for Cache {
+    async fn handle(&self, req: &mut RequestBody, res: &mut ResponseWriter) -> MiddlewareOutcome {
+        let Some(key) = self.key(req) else {
+            return MiddlewareOutcome::Continue;
+        };
+
+        let hit = match self.entries.lock() {
+            Ok(entries) => entries
+                .get(&key)
+                .filter(|entry| entry.expires > Instant::now())
+                .cloned(),
+            Err(_) => None,
+        };
+
+        let Some(entry) = hit else {
+            req.set_ext(Outer(res.headers.keys().cloned().collect()));
+            return MiddlewareOutcome::Continue;
+        };
+
+        res.status = entry.status;
+        for name in entry.headers.keys() {
+            res.headers.remove(name);
+            for value in entry.headers.get_all(name) {
+                res.headers.append(name, value.clone());
+            }
+        }
+        res.body = entry.body;
+        res.set_header("X-Cache", "HIT");
+        MiddlewareOutcome::Stop
+    }
+
+    async fn after(&self, req: &mut RequestBody, res: &mut ResponseWriter) {
+        if res.has_error()
+            || res.is_complete()
+            || res.is_streaming()
+            || res.status != StatusCode::OK
+            || !storable(req, res)
+            || !self.covers_vary(res)
+        {
+            return;
+        }
+
+        let Some(key) = self.key(req) else {
+            return;
+        };
+
+        let mut headers = res.headers.clone();
+        if let Some(Outer(names)) = req.ext::<Outer>() {
+            for name in names {
+                headers.remove(name);
+            }
+        }
+        headers.remove(hyper::header::DATE);
+
+        if let Ok(mut entries) = self.entries.lock() {
+            let now = Instant::now();
+            entries.retain(|_, entry| entry.expires > now);
+
+            entries.insert(
+                key,
+                Entry {
+                    expires: now + self.ttl,
+                    status: res.status,
+                    headers,
+                    body: res.body.clone(),
+                },
+            );
+        }
+    }
+}
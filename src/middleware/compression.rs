@@ -0,0 +1,103 @@
+use async_trait::async_trait;
+use bytes::Bytes;
+use hyper::header::{
+    CONTENT_ENCODING, CONTENT_LENGTH, CONTENT_RANGE, CONTENT_TYPE, ETAG, HeaderValue, VARY,
+};
+
+use crate::{
+    file,
+    http::StatusCode,
+    request::RequestBody,
+    response::ResponseWriter,
+    types::{Middleware, MiddlewareOutcome},
+};
+
+/// Bodies at least this large are encoded on the blocking pool instead of the worker.
+const BLOCKING_THRESHOLD: usize = 64 * 1024;
+
+#[derive(Clone)]
+pub struct Compression {
+    min_size: usize,
+}
+
+impl Compression {
+    pub fn new() -> Self {
+        Self { min_size: 1024 }
+    }
+
+    pub fn min_size(mut self, bytes: usize) -> Self {
+        self.min_size = bytes;
+        self
+    }
+}
+
+impl Default for Compression {
+    fn default() -> Self {
+        Self::new()
+    }
+}
+
+#[async_trait]
+impl Middleware for Compression {
+    async fn handle(&self, _req: &mut RequestBody, _res: &mut ResponseWriter) -> MiddlewareOutcome {
+        MiddlewareOutcome::Continue
+    }
+
+    async fn after(&self, req: &mut RequestBody, res: &mut ResponseWriter) {
+        if res.is_streaming()
+            || res.body.len() < self.min_size
+            || res.headers.contains_key(CONTENT_ENCODING)
+            || res.headers.contains_key(CONTENT_RANGE)
+            || matches!(
+                res.status,
+                StatusCode::NoContent | StatusCode::NotModified | StatusCode::PartialContent
+            )
+        {
+            return;
+        }
+
+        let mime = res
+            .headers
+            .get(CONTENT_TYPE)
+            .and_then(|v| v.to_str().ok())
+            .unwrap_or("text/plain");
+
+        if !file::compressible(mime, res.body.len() as u64) {
+            return;
+        }
+
+        res.headers
+            .append(VARY, HeaderValue::from_static("Accept-Encoding"));
+
+        let Some(encoding) = file::negotiate(req) else {
+            return;
+        };
+
+        let encoded = if res.body.len() >= BLOCKING_THRESHOLD {
+            let body = res.body.clone();
+            tokio::task::spawn_blocking(move || encoding.encode(&body))
+                .await
+                .map_err(std::io::Error::other)
+                .and_then(|r| r)
+        } else {
+            encoding.encode(&res.body)
+        };
+
+        let Ok(encoded) = encoded else {
+            return;
+        };
+
+        if let Some(tag) = res.headers.get(ETAG).and_then(|v| v.to_str().ok()) {
+            let tag = file::encoded_etag(tag, encoding);
+            res.set_header("ETag", &tag);
+        }
+
+        res.body = Bytes::from(encoded);
+        res.headers.remove(CONTENT_LENGTH);
+        res.headers.insert(
+            CONTENT_ENCODING,
+            HeaderValue::from_static(encoding.as_str()),
+        );
+        res.mark_transformed();
+    }
+}
@@ -0,0 +1,179 @@
+use async_trait::async_trait;
+use hyper::header::{
+    ACCESS_CONTROL_ALLOW_CREDENTIALS, ACCESS_CONTROL_ALLOW_HEADERS, ACCESS_CONTROL_ALLOW_METHODS,
+    ACCESS_CONTROL_ALLOW_ORIGIN, ACCESS_CONTROL_MAX_AGE, ACCESS_CONTROL_REQUEST_HEADERS,
+    ACCESS_CONTROL_REQUEST_METHOD, HeaderValue, ORIGIN, VARY,
+};
+
+use crate::{
+    http::StatusCode,
+    request::RequestBody,
+    response::ResponseWriter,
+    types::{Method, Middleware, MiddlewareOutcome},
+};
+
+#[derive(Clone)]
+pub struct Cors {
+    origins: Vec<String>,
+    methods: Vec<Method>,
+    headers: Vec<String>,
+    reflect_headers: bool,
+    credentials: bool,
+    max_age: Option<u64>,
+}
+
+impl Cors {
+    pub fn new() -> Self {
+        Self {
+            origins: Vec::new(),
+            methods: vec![
+                Method::GET,
+                Method::HEAD,
+                Method::POST,
+                Method::PUT,
+                Method::PATCH,
+                Method::DELETE,
+            ],
+            headers: vec!["content-type".to_string(), "authorization".to_string()],
+            reflect_headers: false,
+            credentials: false,
+            max_age: Some(86400),
+        }
+    }
+
+    pub fn allow_origin(mut self, origin: &str) -> Self {
+        self.origins
+            .push(origin.trim_end_matches('/').to_ascii_lowercase());
+        self
+    }
+
+    pub fn allow_methods(mut self, methods: &[Method]) -> Self {
+        self.methods = methods.to_vec();
+        self
+    }
+
+    pub fn allow_headers(mut self, headers: &[&str]) -> Self {
+        self.headers = headers.iter().map(|h| h.to_ascii_lowercase()).collect();
+        self
+    }
+
+    pub fn reflect_request_headers(mut self, reflect: bool) -> Self {
+        self.reflect_headers = reflect;
+        self
+    }
+
+    pub fn allow_credentials(mut self, credentials: bool) -> Self {
+        self.credentials = credentials;
+        self
+    }
+
+    pub fn max_age(mut self, secs: Option<u64>) -> Self {
+        self.max_age = secs;
+        self
+    }
+
+    fn any_origin(&self) -> bool {
+        self.origins.is_empty() || self.origins.iter().any(|o| o == "*")
+    }
+
+    fn allowed(&self, origin: &str) -> bool {
+        self.any_origin() || self.origins.iter().any(|o| o.eq_ignore_ascii_case(origin))
+    }
+
+    fn apply_origin(&self, origin: &HeaderValue, res: &mut ResponseWriter) {
+        if self.any_origin() {
+            res.headers
+                .insert(ACCESS_CONTROL_ALLOW_ORIGIN, HeaderValue::from_static("*"));
+        } else {
+            res.headers
+                .insert(ACCESS_CONTROL_ALLOW_ORIGIN, origin.clone());
+            res.headers.append(VARY, HeaderValue::from_static("Origin"));
+        }
+
+        if self.credentials {
+            res.headers.insert(
+                ACCESS_CONTROL_ALLOW_CREDENTIALS,
+                HeaderValue::from_static("true"),
+            );
+        }
+    }
+}
+
+impl Default for Cors {
+    fn default() -> Self {
+        Self::new()
+    }
+}
+
+#[async_trait]
+impl Middleware for Cors {
+    async fn handle(&self, req: &mut RequestBody, res: &mut ResponseWriter) -> MiddlewareOutcome {
+        let preflight = req.method() == hyper::Method::OPTIONS
+            && req.headers().contains_key(ACCESS_CONTROL_REQUEST_METHOD);
+
+        if !preflight {
+            return MiddlewareOutcome::Continue;
+        }
+
+        let Some(origin) = req.headers().get(ORIGIN).cloned() else {
+            return MiddlewareOutcome::Continue;
+        };
+
+        let valid = origin.to_str().is_ok_and(|o| self.allowed(o));
+
+        if !valid || (self.credentials && self.any_origin()) {
+            res.status(StatusCode::Forbidden)
+                .send("CORS request rejected");
+            return MiddlewareOutcome::Stop;
+        }
+
+        self.apply_origin(&origin, res);
+
+        let methods = self
+            .methods
+            .iter()
+            .map(|m| m.as_str())
+            .collect::<Vec<_>>()
+            .join(", ");
+        res.set_header("Access-Control-Allow-Methods", &methods);
+
+        let requested = req.headers().get(ACCESS_CONTROL_REQUEST_HEADERS).cloned();
+
+        if self.reflect_headers {
+            if let Some(requested) = requested {
+                res.headers.insert(ACCESS_CONTROL_ALLOW_HEADERS, requested);
+            }
+            res.headers.append(
+                VARY,
+                HeaderValue::from_static("Access-Control-Request-Headers"),
+            );
+        } else if !self.headers.is_empty() {
+            res.set_header("Access-Control-Allow-Headers", &self.headers.join(", "));
+        }
+
+        if let Some(secs) = self.max_age {
+            res.headers
+                .insert(ACCESS_CONTROL_MAX_AGE, HeaderValue::from(secs));
+        }
+
+        res.status(StatusCode::NoContent);
+        MiddlewareOutcome::Stop
+    }
+
+    async fn after(&self, req: &mut RequestBody, res: &mut ResponseWriter) {
+        if res.headers.contains_key(ACCESS_CONTROL_ALLOW_ORIGIN)
+            || res.headers.contains_key(ACCESS_CONTROL_ALLOW_METHODS)
+            || (self.credentials && self.any_origin())
+        {
+            return;
+        }
+
+        let Some(origin) = req.headers().get(ORIGIN).cloned() else {
+            return;
+        };
+
+        if origin.to_str().is_ok_and(|o| self.allowed(o)) {
+            self.apply_origin(&origin, res);
+        }
+    }
+}
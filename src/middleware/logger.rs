@@ -0,0 +1,109 @@
+use std::time::Instant;
+
+use async_trait::async_trait;
+use serde_json::json;
+
+use crate::{
+    request::RequestBody,
+    response::ResponseWriter,
+    types::{Middleware, MiddlewareOutcome},
+};
+
+struct Started(Instant);
+
+#[derive(Clone)]
+pub struct Logger {
+    format: String,
+    json: bool,
+}
+
+impl Logger {
+    pub fn new() -> Self {
+        Self {
+            format: "{method} {path} {status} {bytes} {latency_ms}ms {remote}".to_string(),
+            json: false,
+        }
+    }
+
+    pub fn format(mut self, format: &str) -> Self {
+        self.format = format.to_string();
+        self
+    }
+
+    pub fn json(mut self, json: bool) -> Self {
+        self.json = json;
+        self
+    }
+
+    fn line(&self, req: &RequestBody, res: &ResponseWriter) -> String {
+        let latency = latency_ms(req);
+
+        let status = res.status.as_u16();
+        let bytes = res.body_len();
+        let remote = req.remote_addr().to_string();
+        let request_id = req.request_id().unwrap_or("-");
+
+        if self.json {
+            return json!({
+                "method": req.method().as_str(),
+                "path": req.path(),
+                "status": status,
+                "bytes": bytes,
+                "latency_ms": latency,
+                "remote": remote,
+                "request_id": req.request_id(),
+            })
+            .to_string();
+        }
+
+        let latency = latency
+            .map(|ms| format!("{:.2}", ms))
+            .unwrap_or_else(|| "-".to_string());
+
+        self.format
+            .replace("{method}", req.method().as_str())
+            .replace("{path}", req.path())
+            .replace("{status}", &status.to_string())
+            .replace(
+                "{bytes}",
+                &bytes.map_or_else(|| "-".to_string(), |b| b.to_string()),
+            )
+            .replace("{latency_ms}", &latency)
+            .replace("{remote}", &remote)
+            .replace("{request_id}", request_id)
+    }
+}
+
+fn latency_ms(req: &RequestBody) -> Option<f64> {
+    req.ext::<Started>()
+        .map(|started| (started.0.elapsed().as_secs_f64() * 100_000.0).round() / 100.0)
+}
+
+impl Default for Logger {
+    fn default() -> Self {
+        Self::new()
+    }
+}
+
+#[async_trait]
+impl Middleware for Logger {
+    async fn handle(&self, req: &mut RequestBody, _res: &mut ResponseWriter) -> MiddlewareOutcome {
+        req.set_ext(Started(Instant::now()));
+        MiddlewareOutcome::Continue
+    }
+
+    async fn after(&self, req: &mut RequestBody, res: &mut ResponseWriter) {
+        tracing::info!(
+            method = req.method().as_str(),
+            path = req.path(),
+            status = res.status.as_u16(),
+            bytes = res.body_len(),
+            transformed = res.is_transformed(),
+            latency_ms = latency_ms(req),
+            remote = %req.remote_addr(),
+            request_id = req.request_id(),
+            "{}",
+            self.line(req, res)
+        );
+    }
+}
@@ -0,0 +1,19 @@
+#[cfg(feature = "jwt")]
+pub mod auth;
+pub mod cache;
+pub mod compression;
+pub mod cors;
+pub mod logger;
+pub mod rate_limit;
+pub mod request_id;
+pub mod session;
+
+#[cfg(feature = "jwt")]
+pub use auth::{Auth, Claims};
+pub use cache::Cache;
+pub use compression::Compression;
+pub use cors::Cors;
+pub use logger::Logger;
+pub use rate_limit::RateLimiter;
+pub use request_id::RequestId;
+pub use session::{MemoryStore, Session, SessionData, SessionStore};
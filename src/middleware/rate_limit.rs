@@ -0,0 +1,98 @@
+use std::{
+    collections::HashMap,
+    net::IpAddr,
+    sync::{Arc, Mutex},
+    time::{Duration, Instant},
+};
+
+use async_trait::async_trait;
+
+use crate::{
+    http::StatusCode,
+    request::RequestBody,
+    response::ResponseWriter,
+    types::{Middleware, MiddlewareOutcome},
+};
+
+struct Window {
+    start: Instant,
+    count: u32,
+}
+
+struct State {
+    windows: HashMap<IpAddr, Window>,
+    last_sweep: Instant,
+}
+
+#[derive(Clone)]
+pub struct RateLimiter {
+    limit: u32,
+    window: Duration,
+    state: Arc<Mutex<State>>,
+}
+
+impl RateLimiter {
+    pub fn new(limit: u32, window: Duration) -> Self {
+        Self {
+            limit,
+            window,
+            state: Arc::new(Mutex::new(State {
+                windows: HashMap::new(),
+                last_sweep: Instant::now(),
+            })),
+        }
+    }
+
+    fn check(&self, ip: IpAddr) -> Result<(), Duration> {
+        let Ok(mut state) = self.state.lock() else {
+            return Ok(());
+        };
+
+        let now = Instant::now();
+
+        if now.duration_since(state.last_sweep) >= self.window {
+            let window = self.window;
+            state
+                .windows
+                .retain(|_, w| now.duration_since(w.start) < window);
+            state.last_sweep = now;
+        }
+
+        let entry = state.windows.entry(ip).or_insert(Window {
+            start: now,
+            count: 0,
+        });
+
+        if now.duration_since(entry.start) >= self.window {
+            entry.start = now;
+            entry.count = 0;
+        }
+
+        if entry.count >= self.limit {
+            return Err(self.window - now.duration_since(entry.start));
+        }
+
+        entry.count += 1;
+        Ok(())
+    }
+}
+
+#[async_trait]
+impl Middleware for RateLimiter {
+    async fn handle(&self, req: &mut RequestBody, res: &mut ResponseWriter) -> MiddlewareOutcome {
+        let ip = req.client_ip();
+
+        match self.check(ip) {
+            Ok(()) => MiddlewareOutcome::Continue,
+            Err(wait) => {
+                let secs = wait.as_secs() + u64::from(wait.subsec_nanos() > 0);
+
+                res.status(StatusCode::TooManyRequests)
+                    .set_header("Retry-After", &secs.max(1).to_string())
+                    .send("Too Many Requests");
+
+                MiddlewareOutcome::Stop
+            }
+        }
+    }
+}
@@ -0,0 +1,75 @@
+use async_trait::async_trait;
+use hyper::header::{HeaderName, HeaderValue};
+use uuid::Uuid;
+
+use crate::{
+    request::RequestBody,
+    response::ResponseWriter,
+    types::{Middleware, MiddlewareOutcome},
+};
+
+#[derive(Clone)]
+pub struct RequestId {
+    header: HeaderName,
+    trust_incoming: bool,
+}
+
+impl RequestId {
+    pub fn new() -> Self {
+        Self {
+            header: HeaderName::from_static("x-request-id"),
+            trust_incoming: true,
+        }
+    }
+
+    pub fn header(mut self, name: &str) -> Self {
+        if let Ok(name) = HeaderName::from_bytes(name.as_bytes()) {
+            self.header = name;
+        }
+        self
+    }
+
+    pub fn trust_incoming(mut self, trust: bool) -> Self {
+        self.trust_incoming = trust;
+        self
+    }
+
+    fn incoming(&self, req: &RequestBody) -> Option<String> {
+        let id = req.headers().get(&self.header)?.to_str().ok()?.trim();
+
+        let valid = !id.is_empty()
+            && id.len() <= 128
+            && id
+                .bytes()
+                .all(|b| b.is_ascii_alphanumeric() || b"-_.:".contains(&b));
+
+        valid.then(|| id.to_string())
+    }
+}
+
+impl Default for RequestId {
+    fn default() -> Self {
+        Self::new()
+    }
+}
+
+#[async_trait]
+impl Middleware for RequestId {
+    async fn handle(&self, req: &mut RequestBody, res: &mut ResponseWriter) -> MiddlewareOutcome {
+        let id = self
+            .incoming(req)
+            .filter(|_| self.trust_incoming)
+            .unwrap_or_else(|| Uuid::new_v4().to_string());
+
+        tracing::Span::current().record("request_id", id.as_str());
+
+        req.set_request_id(&id);
+        res.set_request_id(&id);
+
+        if let Ok(value) = HeaderValue::from_str(&id) {
+            res.headers.insert(self.header.clone(), value);
+        }
+
+        MiddlewareOutcome::Continue
+    }
+}
@@ -0,0 +1,277 @@
+use std::{
+    collections::HashMap,
+    sync::{Arc, Mutex},
+    time::{Duration, Instant},
+};
+
+use async_trait::async_trait;
+use cookie::{Cookie, CookieJar, Key, SameSite};
+use serde::{Serialize, de::DeserializeOwned};
+use serde_json::Value;
+use uuid::Uuid;
+
+use crate::{
+    request::RequestBody,
+    response::ResponseWriter,
+    types::{BoltError, Middleware, MiddlewareOutcome},
+};
+
+pub type SessionMap = HashMap<String, Value>;
+
+#[async_trait]
+pub trait SessionStore: Send + Sync {
+    async fn load(&self, id: &str) -> Option<SessionMap>;
+    async fn store(&self, id: &str, data: SessionMap, ttl: Duration);
+    async fn destroy(&self, id: &str);
+}
+
+#[derive(Clone, Default)]
+pub struct MemoryStore {
+    sessions: Arc<Mutex<HashMap<String, (Instant, SessionMap)>>>,
+}
+
+impl MemoryStore {
+    pub fn new() -> Self {
+        Self::default()
+    }
+}
+
+#[async_trait]
+impl SessionStore for MemoryStore {
+    async fn load(&self, id: &str) -> Option<SessionMap> {
+        let sessions = self.sessions.lock().ok()?;
+
+        sessions
+            .get(id)
+            .filter(|(expires, _)| *expires > Instant::now())
+            .map(|(_, data)| data.clone())
+    }
+
+    async fn store(&self, id: &str, data: SessionMap, ttl: Duration) {
+        if let Ok(mut sessions) = self.sessions.lock() {
+            let now = Instant::now();
+            sessions.retain(|_, (expires, _)| *expires > now);
+            sessions.insert(id.to_string(), (now + ttl, data));
+        }
+    }
+
+    async fn destroy(&self, id: &str) {
+        if let Ok(mut sessions) = self.sessions.lock() {
+            sessions.remove(id);
+        }
+    }
+}
+
+#[derive(Default)]
+struct State {
+    id: Option<String>,
+    data: SessionMap,
+    changed: bool,
+    regenerate: bool,
+    destroyed: bool,
+}
+
+#[derive(Clone, Default)]
+pub struct SessionData {
+    state: Arc<Mutex<State>>,
+}
+
+impl SessionData {
+    fn load(id: Option<String>, data: SessionMap) -> Self {
+        Self {
+            state: Arc::new(Mutex::new(State {
+                id,
+                data,
+                ..Default::default()
+            })),
+        }
+    }
+
+    pub fn id(&self) -> Option<String> {
+        self.state.lock().ok()?.id.clone()
+    }
+
+    pub fn get<T: DeserializeOwned>(&self, key: &str) -> Option<T> {
+        let state = self.state.lock().ok()?;
+        serde_json::from_value(state.data.get(key)?.clone()).ok()
+    }
+
+    pub fn insert<T: Serialize>(&self, key: &str, value: T) {
+        let Ok(value) = serde_json::to_value(value) else {
+            return;
+        };
+
+        if let Ok(mut state) = self.state.lock() {
+            state.data.insert(key.to_string(), value);
+            state.changed = true;
+        }
+    }
+
+    pub fn remove(&self, key: &str) {
+        if let Ok(mut state) = self.state.lock()
+            && state.data.remove(key).is_some()
+        {
+            state.changed = true;
+        }
+    }
+
+    pub fn clear(&self) {
+        if let Ok(mut state) = self.state.lock() {
+            state.data.clear();
+            state.changed = true;
+        }
+    }
+
+    pub fn regenerate(&self) {
+        if let Ok(mut state) = self.state.lock() {
+            state.regenerate = true;
+            state.changed = true;
+        }
+    }
+
+    pub fn destroy(&self) {
+        if let Ok(mut state) = self.state.lock() {
+            state.data.clear();
+            state.destroyed = true;
+        }
+    }
+}
+
+#[derive(Clone)]
+pub struct Session {
+    store: Arc<dyn SessionStore>,
+    key: Key,
+    cookie_name: String,
+    ttl: Duration,
+    secure: bool,
+}
+
+impl Session {
+    pub fn new<S: SessionStore + 'static>(store: S, secret: &[u8]) -> Result<Self, BoltError> {
+        if secret.len() < 32 {
+            return Err("Session secret must be at least 32 bytes".into());
+        }
+
+        Ok(Self {
+            store: Arc::new(store),
+            key: Key::derive_from(secret),
+            cookie_name: "bolt.sid".to_string(),
+            ttl: Duration::from_secs(24 * 60 * 60),
+            secure: true,
+        })
+    }
+
+    pub fn cookie_name(mut self, name: &str) -> Self {
+        self.cookie_name = name.to_string();
+        self
+    }
+
+    pub fn ttl(mut self, ttl: Duration) -> Self {
+        self.ttl = ttl;
+        self
+    }
+
+    pub fn secure(mut self, secure: bool) -> Self {
+        self.secure = secure;
+        self
+    }
+
+    fn verify(&self, value: &str) -> Option<String> {
+        let mut jar = CookieJar::new();
+        jar.add_original(Cookie::new(self.cookie_name.clone(), value.to_string()));
+
+        jar.signed(&self.key)
+            .get(&self.cookie_name)
+            .map(|c| c.value().to_string())
+    }
+
+    fn sign(&self, id: &str) -> String {
+        let mut jar = CookieJar::new();
+        jar.signed_mut(&self.key)
+            .add(Cookie::new(self.cookie_name.clone(), id.to_string()));
+
+        jar.get(&self.cookie_name)
+            .map(|c| c.value().to_string())
+            .unwrap_or_default()
+    }
+
+    fn set_cookie(&self, res: &mut ResponseWriter, value: &str, max_age: i64) {
+        let mut cookie = res
+            .set_cookie(&self.cookie_name, value)
+            .max_age(Duration::from_secs(max_age.max(0) as u64))
+            .http_only()
+            .same_site(SameSite::Lax);
+
+        if self.secure {
+            cookie = cookie.secure();
+        }
+
+        cookie.finish();
+    }
+}
+
+#[async_trait]
+impl Middleware for Session {
+    async fn handle(&self, req: &mut RequestBody, _res: &mut ResponseWriter) -> MiddlewareOutcome {
+        let id = req
+            .get_cookie(&self.cookie_name)
+            .and_then(|value| self.verify(&value));
+
+        let session = match id {
+            Some(id) => match self.store.load(&id).await {
+                Some(data) => SessionData::load(Some(id), data),
+                None => SessionData::default(),
+            },
+            None => SessionData::default(),
+        };
+
+        req.set_ext(session);
+        MiddlewareOutcome::Continue
+    }
+
+    async fn after(&self, req: &mut RequestBody, res: &mut ResponseWriter) {
+        let Some(session) = req.ext::<SessionData>() else {
+            return;
+        };
+
+        let (id, data, regenerate, destroyed) = {
+            let Ok(mut state) = session.state.lock() else {
+                return;
+            };
+
+            if !state.changed && !state.destroyed {
+                return;
+            }
+
+            (
+                state.id.take(),
+                std::mem::take(&mut state.data),
+                state.regenerate,
+                state.destroyed,
+            )
+        };
+
+        if destroyed {
+            if let Some(id) = id {
+                self.store.destroy(&id).await;
+            }
+            self.set_cookie(res, "", 0);
+            return;
+        }
+
+        let id = match id {
+            Some(id) if !regenerate => id,
+            old => {
+                if let Some(old) = old {
+                    self.store.destroy(&old).await;
+                }
+                Uuid::new_v4().to_string()
+            }
+        };
+
+        self.store.store(&id, data, self.ttl).await;
+
+        let signed = self.sign(&id);
+        self.set_cookie(res, &signed, self.ttl.as_secs() as i64);
+    }
+}
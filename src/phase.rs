@@ -0,0 +1,62 @@
+use std::{
+    future::Future,
+    sync::{
+        Arc,
+        atomic::{AtomicUsize, Ordering},
+    },
+};
+
+use crate::types::TimeoutKind;
+
+#[derive(Default)]
+pub(crate) struct Phases {
+    body: AtomicUsize,
+    upstream: AtomicUsize,
+}
+
+impl Phases {
+    pub(crate) fn current(&self) -> TimeoutKind {
+        if self.body.load(Ordering::Acquire) > 0 {
+            TimeoutKind::Body
+        } else if self.upstream.load(Ordering::Acquire) > 0 {
+            TimeoutKind::Upstream
+        } else {
+            TimeoutKind::Handler
+        }
+    }
+
+    fn counter(&self, kind: TimeoutKind) -> Option<&AtomicUsize> {
+        match kind {
+            TimeoutKind::Body => Some(&self.body),
+            TimeoutKind::Upstream => Some(&self.upstream),
+            TimeoutKind::Handler => None,
+        }
+    }
+}
+
+tokio::task_local! {
+    static PHASES: Arc<Phases>;
+}
+
+pub(crate) async fn scope<F: Future>(phases: Arc<Phases>, fut: F) -> F::Output {
+    PHASES.scope(phases, fut).await
+}
+
+pub(crate) struct PhaseGuard {
+    phases: Arc<Phases>,
+    kind: TimeoutKind,
+}
+
+pub(crate) fn enter(kind: TimeoutKind) -> Option<PhaseGuard> {
+    let phases = PHASES.try_with(|p| p.clone()).ok()?;
+    phases.counter(kind)?.fetch_add(1, Ordering::AcqRel);
+    Some(PhaseGuard { phases, kind })
+}
+
+impl Drop for PhaseGuard {
+    fn drop(&mut self) {
+        if let Some(counter) = self.phases.counter(self.kind) {
+            counter.fetch_sub(1, Ordering::AcqRel);
+        }
+    }
+}
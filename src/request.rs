@@ -1,39 +1,101 @@
 use bytes::Bytes;
-use futures_util::TryStreamExt;
+use cookie::{Cookie, CookieJar, Key};
+use futures_util::{
+    Stream, TryStreamExt,
+    future::{self, Either},
+    stream,
+};
 use http_body_util::{BodyExt, BodyStream};
 use hyper::header::HeaderName;
+use hyper::http::request::Parts;
 use hyper::{Request, Uri, Version, body::Incoming, header::HeaderValue};
 use mime::Mime;
 use multer::Multipart;
 use serde::de::DeserializeOwned;
+use std::any::{Any, TypeId};
 use std::collections::HashMap;
-use std::net::SocketAddr;
+use std::net::{IpAddr, SocketAddr};
+use std::path::Path;
+use std::str::FromStr;
+use std::sync::{Arc, OnceLock};
+use std::time::Duration;
 use tokio::io::AsyncWriteExt;
 use url::form_urlencoded;
 use uuid::Uuid;
 
-use crate::types::{BoltError, FormData, FormFile};
+use crate::{
+    body::{self, LimitedBody},
+    http::{HttpError, StatusCode},
+    types::{BoltError, FormData, FormDataConfig, FormFile, Method, TlsInfo},
+};
+
+#[derive(Debug, Clone)]
+pub(crate) struct RequestConfig {
+    pub trusted_proxies: Vec<IpAddr>,
+    pub body_read_timeout: Duration,
+    pub upload_mode: u32,
+    pub form: FormDataConfig,
+    pub upload_orphan_ttl: Duration,
+    pub body_limit: Option<usize>,
+    pub body_limits: HashMap<String, usize>,
+    pub cookie_key: Option<Key>,
+    pub public_url: Option<url::Url>,
+}
+
+impl Default for RequestConfig {
+    fn default() -> Self {
+        Self {
+            trusted_proxies: Vec::new(),
+            body_read_timeout: Duration::from_secs(30),
+            upload_mode: 0o600,
+            form: FormDataConfig::default(),
+            upload_orphan_ttl: Duration::from_secs(60 * 60),
+            body_limit: Some(2 * 1024 * 1024),
+            body_limits: HashMap::new(),
+            cookie_key: None,
+            public_url: None,
+        }
+    }
+}
 
 #[allow(dead_code)]
 pub struct RequestBody {
-    pub inner: Option<Request<Incoming>>,
+    parts: Parts,
+    body: Option<Incoming>,
     pub raw_body: Option<Bytes>,
     params: HashMap<String, String>,
+    cookies: OnceLock<HashMap<String, String>>,
+    route: Option<String>,
+    route_methods: Vec<Method>,
     form_data_result: Option<Result<FormData, Box<dyn std::error::Error + Send + Sync>>>,
     temp_paths: Vec<String>,
     socket: SocketAddr,
+    tls: Option<Arc<TlsInfo>>,
+    config: Arc<RequestConfig>,
+    extensions: HashMap<TypeId, Box<dyn Any + Send + Sync>>,
+    request_id: Option<String>,
     pub extended: bool,
 }
 
 #[allow(dead_code)]
 impl RequestBody {
     pub fn new(req: Request<Incoming>, socket: SocketAddr) -> Self {
+        let (parts, body) = req.into_parts();
+
         Self {
-            inner: Some(req),
+            parts,
+            body: Some(body),
             params: HashMap::new(),
+            cookies: OnceLock::new(),
+            route: None,
+            route_methods: Vec::new(),
             form_data_result: None,
             temp_paths: Vec::new(),
             socket,
+            tls: None,
+            config: Arc::new(RequestConfig::default()),
+            extensions: HashMap::new(),
+            request_id: None,
             extended: false,
             raw_body: None,
         }
@@ -47,74 +109,226 @@ impl RequestBody {
         &self.socket
     }
 
+    /// The client's address. Behind a trusted proxy this is the nearest untrusted hop of
+    /// `X-Forwarded-For`, so a client can't pick its own address by prepending hops.
+    pub fn client_ip(&self) -> IpAddr {
+        let peer = self.socket.ip();
+
+        if !self.via_trusted_proxy() {
+            return peer;
+        }
+
+        let hops: Vec<IpAddr> = self
+            .parts
+            .headers
+            .get_all("x-forwarded-for")
+            .iter()
+            .filter_map(|v| v.to_str().ok())
+            .flat_map(|v| v.split(','))
+            .filter_map(|hop| hop.trim().parse().ok())
+            .collect();
+
+        hops.iter()
+            .rev()
+            .find(|hop| !self.config.trusted_proxies.contains(hop))
+            .or(hops.first())
+            .copied()
+            .unwrap_or(peer)
+    }
+
+    pub fn tls(&self) -> Option<&TlsInfo> {
+        self.tls.as_deref()
+    }
+
+    pub fn sni(&self) -> Option<&str> {
+        self.tls.as_ref()?.sni.as_deref()
+    }
+
+    pub(crate) fn set_tls(&mut self, tls: Option<Arc<TlsInfo>>) {
+        if let Some(cert) = tls.as_ref().and_then(|t| t.client_cert.clone()) {
+            self.set_ext(cert);
+        }
+        self.tls = tls;
+    }
+
+    pub(crate) fn set_config(&mut self, config: Arc<RequestConfig>) {
+        self.config = config;
+    }
+
     pub fn param(&self, key: &str) -> String {
         self.params.get(key).cloned().unwrap_or_default()
     }
 
+    pub fn param_opt(&self, key: &str) -> Option<String> {
+        self.params.get(key).cloned()
+    }
+
+    pub fn param_as<T>(&self, key: &str) -> Result<T, BoltError>
+    where
+        T: FromStr,
+        T::Err: std::fmt::Display,
+    {
+        let value = self.params.get(key).ok_or_else(|| {
+            let msg = format!("Missing path parameter '{}'", key);
+            Box::new(HttpError::new(StatusCode::BadRequest, msg)) as BoltError
+        })?;
+
+        value.parse().map_err(|e: T::Err| {
+            let msg = format!("Invalid path parameter '{}' ({:?}): {}", key, value, e);
+            Box::new(HttpError::new(StatusCode::BadRequest, msg)) as BoltError
+        })
+    }
+
     pub(crate) fn set_params(&mut self, params: HashMap<String, String>) {
         self.params = params;
     }
 
+    pub fn request_id(&self) -> Option<&str> {
+        self.request_id.as_deref()
+    }
+
+    pub fn set_request_id(&mut self, id: &str) {
+        self.request_id = Some(id.to_string());
+    }
+
+    pub fn set_ext<T: Send + Sync + 'static>(&mut self, value: T) {
+        self.extensions.insert(TypeId::of::<T>(), Box::new(value));
+    }
+
+    pub fn ext<T: Send + Sync + 'static>(&self) -> Option<&T> {
+        self.extensions
+            .get(&TypeId::of::<T>())
+            .and_then(|value| value.downcast_ref::<T>())
+    }
+
+    pub fn ext_mut<T: Send + Sync + 'static>(&mut self) -> Option<&mut T> {
+        self.extensions
+            .get_mut(&TypeId::of::<T>())
+            .and_then(|value| value.downcast_mut::<T>())
+    }
+
+    pub fn remove_ext<T: Send + Sync + 'static>(&mut self) -> Option<T> {
+        self.extensions
+            .remove(&TypeId::of::<T>())
+            .and_then(|value| value.downcast::<T>().ok())
+            .map(|value| *value)
+    }
+
+    pub fn route(&self) -> Option<&str> {
+        self.route.as_deref()
+    }
+
+    pub fn allowed_methods(&self) -> &[Method] {
+        &self.route_methods
+    }
+
+    pub(crate) fn set_route(&mut self, route: &str, methods: Vec<Method>) {
+        self.route = Some(route.to_string());
+        self.route_methods = methods;
+    }
+
     pub fn method(&self) -> &hyper::Method {
-        self.inner
-            .as_ref()
-            .expect("Cannot access method, request body was consumed.")
-            .method()
+        &self.parts.method
     }
 
     pub fn path(&self) -> &str {
-        self.inner
-            .as_ref()
-            .expect("Cannot access path, request body was consumed.")
-            .uri()
-            .path()
+        self.parts.uri.path()
     }
 
     pub fn headers(&self) -> &hyper::HeaderMap {
-        self.inner
-            .as_ref()
-            .expect("Cannot access headers, request body was consumed.")
-            .headers()
+        &self.parts.headers
     }
 
     pub fn set_headers(&mut self, key: &str, value: &str) {
-        let key = HeaderName::from_bytes(key.as_bytes()).expect("Invalid header name");
-        let value = HeaderValue::from_str(value).expect("Invalid header value");
-
-        self.inner
-            .as_mut()
-            .expect("Cannot set headers, request body was consumed.")
-            .headers_mut()
-            .insert(key, value);
+        match (
+            HeaderName::from_bytes(key.as_bytes()),
+            HeaderValue::from_str(value),
+        ) {
+            (Ok(key), Ok(value)) => {
+                self.parts.headers.insert(key, value);
+            }
+            _ => tracing::warn!(header = key, "Skipping invalid request header"),
+        }
     }
 
     pub fn get_headers(&mut self, key: &str) -> Option<&HeaderValue> {
-        self.inner
-            .as_ref()
-            .expect("Cannot access headers, request body was consumed.")
-            .headers()
-            .get(key)
+        self.parts.headers.get(key)
+    }
+
+    pub fn host(&self) -> Option<&str> {
+        self.parts
+            .headers
+            .get(hyper::header::HOST)
+            .and_then(|v| v.to_str().ok())
+            .or_else(|| self.parts.uri.host())
+    }
+
+    fn via_trusted_proxy(&self) -> bool {
+        self.config.trusted_proxies.contains(&self.socket.ip())
+    }
+
+    pub fn is_secure(&self) -> bool {
+        if self.tls.is_some() {
+            return true;
+        }
+
+        if !self.via_trusted_proxy() {
+            return false;
+        }
+
+        self.parts
+            .headers
+            .get("x-forwarded-proto")
+            .and_then(|v| v.to_str().ok())
+            .and_then(|v| v.split(',').next())
+            .is_some_and(|proto| proto.trim().eq_ignore_ascii_case("https"))
+    }
+
+    pub fn base_url(&self) -> Option<String> {
+        let forwarded = self
+            .parts
+            .headers
+            .get("x-forwarded-host")
+            .filter(|_| self.via_trusted_proxy())
+            .and_then(|v| v.to_str().ok())
+            .and_then(|v| v.split(',').next())
+            .map(|v| v.trim());
+
+        let host = forwarded.or_else(|| self.host())?;
+
+        let scheme = if self.is_secure() { "https" } else { "http" };
+        Some(format!("{}://{}", scheme, host))
+    }
+
+    pub fn absolute_url(&self, location: &str) -> Option<String> {
+        let current = self.current_url()?;
+        current.join(location).ok().map(|u| u.to_string())
+    }
+
+    pub(crate) fn current_url(&self) -> Option<url::Url> {
+        url::Url::parse(&format!("{}{}", self.base_url()?, self.path())).ok()
+    }
+
+    /// The current request under the configured public URL. Unlike `current_url` this
+    /// never trusts the client's `Host`, so it is safe to put into a `Location`.
+    pub(crate) fn public_url(&self) -> Option<url::Url> {
+        let mut url = self.config.public_url.clone()?;
+        let path = format!("{}{}", url.path().trim_end_matches('/'), self.path());
+        url.set_path(&path);
+        Some(url)
     }
 
     pub fn uri(&self) -> &Uri {
-        self.inner
-            .as_ref()
-            .expect("Cannot access uri, request body was consumed.")
-            .uri()
+        &self.parts.uri
     }
 
     pub fn version(&self) -> Version {
-        self.inner
-            .as_ref()
-            .expect("Cannot access version, request body was consumed.")
-            .version()
+        self.parts.version
     }
 
     pub fn query(&self) -> HashMap<String, String> {
-        self.inner
-            .as_ref()
-            .expect("Cannot access uri, request body was consumed.")
-            .uri()
+        self.parts
+            .uri
             .query()
             .map(|q| {
                 form_urlencoded::parse(q.as_bytes())
@@ -124,25 +338,109 @@ impl RequestBody {
             .unwrap_or_default()
     }
 
+    pub fn query_as<T: DeserializeOwned>(&self) -> Result<T, BoltError> {
+        let query = self.parts.uri.query();
+
+        serde_html_form::from_str(query.unwrap_or_default()).map_err(|e| {
+            let msg = match query {
+                Some(_) => format!("Invalid query string: {}", e),
+                None => format!("Missing query string: {}", e),
+            };
+            Box::new(HttpError::new(StatusCode::BadRequest, msg)) as BoltError
+        })
+    }
+
     pub fn query_param(&self, key: &str) -> Option<String> {
         let query_params = self.query();
         query_params.get(key).cloned()
     }
 
-    pub async fn bytes(&mut self) -> Result<Bytes, hyper::Error> {
+    pub async fn bytes(&mut self) -> Result<Bytes, BoltError> {
         if let Some(raw) = &self.raw_body {
             return Ok(raw.clone());
         }
 
-        let req: Request<Incoming> = self
-            .inner
-            .take()
-            .expect("Request body has already been consumed.");
+        let body = self.limited_body()?;
+
+        let bytes = body.collect().await?.to_bytes();
+        self.raw_body = Some(bytes.clone());
+        Ok(bytes)
+    }
+
+    pub fn body_stream(
+        &mut self,
+    ) -> Result<impl Stream<Item = Result<Bytes, BoltError>> + Send + 'static, BoltError> {
+        if let Some(raw) = &self.raw_body {
+            return Ok(Either::Left(stream::once(future::ready(Ok(raw.clone())))));
+        }
+
+        let body = self.limited_body()?;
+
+        Ok(Either::Right(BodyStream::new(body).try_filter_map(
+            |frame| async move { Ok(frame.into_data().ok()) },
+        )))
+    }
+
+    pub fn content_type(&self) -> Option<Mime> {
+        self.parts
+            .headers
+            .get(hyper::header::CONTENT_TYPE)?
+            .to_str()
+            .ok()?
+            .parse()
+            .ok()
+    }
+
+    pub fn charset(&self) -> Option<String> {
+        self.content_type()?
+            .get_param(mime::CHARSET)
+            .map(|c| c.as_str().to_ascii_lowercase())
+    }
+
+    pub fn body_limit(&self) -> Option<usize> {
+        let essence = self
+            .parts
+            .headers
+            .get(hyper::header::CONTENT_TYPE)
+            .and_then(|v| v.to_str().ok())
+            .and_then(|v| v.split(';').next())
+            .map(|v| v.trim().to_ascii_lowercase());
+
+        match essence {
+            Some(e) => match self.config.body_limits.get(&e) {
+                Some(limit) => Some(*limit),
+                // Uploads are capped by `FormDataConfig` instead.
+                None if e == "multipart/form-data" => None,
+                None => self.config.body_limit,
+            },
+            None => self.config.body_limit,
+        }
+    }
+
+    fn limited_body(&mut self) -> Result<LimitedBody, BoltError> {
+        let limit = self.body_limit();
+
+        if let Some(limit) = limit {
+            let declared = self
+                .parts
+                .headers
+                .get(hyper::header::CONTENT_LENGTH)
+                .and_then(|v| v.to_str().ok())
+                .and_then(|v| v.parse::<u64>().ok());
+
+            if declared.is_some_and(|len| len > limit as u64) {
+                return Err(body::too_large(limit));
+            }
+        }
 
-        let (_, body) = req.into_parts();
+        let body = self.body.take().ok_or_else(|| {
+            Box::new(HttpError::new(
+                StatusCode::InternalServerError,
+                "Request body has already been consumed",
+            )) as BoltError
+        })?;
 
-        let collected = body.collect().await?;
-        Ok(collected.to_bytes())
+        Ok(LimitedBody::new(body, self.config.body_read_timeout, limit))
     }
 
     pub async fn text(&mut self) -> Result<String, BoltError> {
@@ -152,10 +450,30 @@ impl RequestBody {
     }
 
     pub async fn json<T: DeserializeOwned>(&mut self) -> Result<T, BoltError> {
+        self.expect_json()?;
         let bytes = self.bytes().await?;
         Ok(serde_json::from_slice(&bytes)?)
     }
 
+    pub async fn json_with_raw<T: DeserializeOwned>(&mut self) -> Result<(T, Bytes), BoltError> {
+        self.expect_json()?;
+        let bytes = self.bytes().await?;
+        let value = serde_json::from_slice(&bytes)?;
+        Ok((value, bytes))
+    }
+
+    fn expect_json(&self) -> Result<(), BoltError> {
+        match self.content_type() {
+            Some(mime) if mime.subtype() != mime::JSON && mime.suffix() != Some(mime::JSON) => {
+                Err(Box::new(HttpError::new(
+                    StatusCode::UnsupportedMediaType,
+                    format!("Expected a JSON body, got {}", mime.essence_str()),
+                )))
+            }
+            _ => Ok(()),
+        }
+    }
+
     pub async fn urlencoded(&mut self) -> Result<serde_json::Value, BoltError> {
         let bytes = self.bytes().await?;
 
@@ -172,29 +490,158 @@ impl RequestBody {
         }
     }
 
+    pub fn cookies(&self) -> HashMap<String, String> {
+        self.cookie_map().clone()
+    }
+
     pub fn get_cookie(&self, name: &str) -> Option<String> {
-        self.inner
-            .as_ref()
-            .expect("Request body has already been consumed")
-            .headers()
-            .get(hyper::header::COOKIE)?
-            .to_str()
-            .ok()
-            .and_then(|cookie_header| {
-                cookie_header.split(';').map(|s| s.trim()).find_map(|pair| {
-                    let mut parts = pair.splitn(2, '=');
-                    let key = parts.next()?;
-                    let value = parts.next()?;
-                    if key == name {
-                        Some(value.to_string())
-                    } else {
-                        None
+        self.cookie_map().get(name).cloned()
+    }
+
+    pub fn get_signed_cookie(&self, name: &str) -> Option<String> {
+        self.verified_cookie(name, false)
+    }
+
+    pub fn get_private_cookie(&self, name: &str) -> Option<String> {
+        self.verified_cookie(name, true)
+    }
+
+    fn verified_cookie(&self, name: &str, private: bool) -> Option<String> {
+        let key = self.config.cookie_key.as_ref()?;
+        let value = self.get_cookie(name)?;
+
+        let mut jar = CookieJar::new();
+        jar.add_original(Cookie::new(name.to_string(), value));
+
+        let cookie = if private {
+            jar.private(key).get(name)
+        } else {
+            jar.signed(key).get(name)
+        };
+
+        cookie.map(|c| c.value().to_string())
+    }
+
+    fn cookie_map(&self) -> &HashMap<String, String> {
+        self.cookies.get_or_init(|| {
+            let mut cookies = HashMap::new();
+
+            for header in self.parts.headers.get_all(hyper::header::COOKIE) {
+                let Ok(header) = header.to_str() else {
+                    continue;
+                };
+
+                for pair in header.split(';') {
+                    let Some((key, value)) = pair.split_once('=') else {
+                        continue;
+                    };
+
+                    let key = key.trim();
+                    if key.is_empty() {
+                        continue;
                     }
-                })
+
+                    let value = value.trim();
+                    let value = value
+                        .strip_prefix('"')
+                        .and_then(|v| v.strip_suffix('"'))
+                        .unwrap_or(value);
+
+                    cookies
+                        .entry(key.to_string())
+                        .or_insert_with(|| value.to_string());
+                }
+            }
+
+            cookies
+        })
+    }
+
+    fn accept_ranges(&self) -> Vec<(String, f32)> {
+        self.parts
+            .headers
+            .get(hyper::header::ACCEPT)
+            .and_then(|v| v.to_str().ok())
+            .map(|header| {
+                quality_entries(header)
+                    .into_iter()
+                    .filter(|(range, _)| {
+                        range.split_once('/').is_some_and(|(t, s)| {
+                            !t.is_empty() && !s.is_empty() && !s.contains('/')
+                        })
+                    })
+                    .collect()
             })
+            .unwrap_or_default()
+    }
+
+    pub fn accepts(&self, mime: &str) -> bool {
+        let ranges = self.accept_ranges();
+        ranges.is_empty() || media_match(&ranges, mime).is_some_and(|(q, _, _)| q > 0.0)
+    }
+
+    pub fn preferred(&self, offered: &[&str]) -> Option<String> {
+        let ranges = self.accept_ranges();
+        if ranges.is_empty() {
+            return offered.first().map(|s| s.to_string());
+        }
+
+        let mut best: Option<(&str, (f32, u8, usize))> = None;
+        for mime in offered {
+            let Some(found) = media_match(&ranges, mime).filter(|(q, _, _)| *q > 0.0) else {
+                continue;
+            };
+
+            let better = best.is_none_or(|(_, (q, specificity, index))| {
+                found.0 > q
+                    || (found.0 == q && found.1 > specificity)
+                    || (found.0 == q && found.1 == specificity && found.2 < index)
+            });
+
+            if better {
+                best = Some((mime, found));
+            }
+        }
+
+        best.map(|(mime, _)| mime.to_string())
+    }
+
+    pub fn preferred_language(&self, supported: &[&str]) -> Option<String> {
+        let header = self
+            .parts
+            .headers
+            .get(hyper::header::ACCEPT_LANGUAGE)?
+            .to_str()
+            .ok()?;
+
+        for (tag, _) in parse_quality_list(header) {
+            if tag == "*" {
+                return supported.first().map(|s| s.to_string());
+            }
+
+            if let Some(lang) = supported.iter().find(|s| s.eq_ignore_ascii_case(&tag)) {
+                return Some(lang.to_string());
+            }
+
+            let matched = supported.iter().find(|s| {
+                let s = s.to_ascii_lowercase();
+                s.starts_with(&format!("{}-", tag)) || tag.starts_with(&format!("{}-", s))
+            });
+
+            if let Some(lang) = matched {
+                return Some(lang.to_string());
+            }
+        }
+
+        None
     }
 
     pub async fn form_data(&mut self) -> Result<FormData, BoltError> {
+        let config = self.config.form.clone();
+        self.form_data_with(&config).await
+    }
+
+    pub async fn form_data_with(&mut self, config: &FormDataConfig) -> Result<FormData, BoltError> {
         if let Some(Ok(fd)) = &self.form_data_result {
             return Ok(fd.clone());
         }
@@ -205,13 +652,7 @@ impl RequestBody {
             )));
         }
 
-        let header_opt = {
-            let req_ref = self
-                .inner
-                .as_ref()
-                .expect("Request body was consumed before form_data call.");
-            req_ref.headers().get(hyper::header::CONTENT_TYPE).cloned()
-        };
+        let header_opt = self.parts.headers.get(hyper::header::CONTENT_TYPE).cloned();
 
         let content_type = match header_opt {
             Some(header_value) => header_value.to_str()?.parse::<Mime>()?,
@@ -233,39 +674,94 @@ impl RequestBody {
             .ok_or("Missing boundary parameter in Content-Type")?
             .to_string();
 
-        let (_, body) = self
-            .inner
-            .take()
-            .expect("Request already consumed")
-            .into_parts();
+        let body = self.limited_body()?;
 
         let stream =
             BodyStream::new(body).try_filter_map(|frame| async move { Ok(frame.into_data().ok()) });
 
-        let mut multipart = Multipart::new(stream, boundary);
+        let multipart = Multipart::new(stream, boundary);
+
+        match self.read_multipart(multipart, config).await {
+            Ok(form_data) => {
+                self.form_data_result = Some(Ok(form_data.clone()));
+                Ok(form_data)
+            }
+            Err(e) => {
+                self.cleanup().await;
+                self.form_data_result = Some(Err(e.to_string().into()));
+                Err(e)
+            }
+        }
+    }
 
+    async fn read_multipart(
+        &mut self,
+        mut multipart: Multipart<'static>,
+        config: &FormDataConfig,
+    ) -> Result<FormData, BoltError> {
         let mut form_data = FormData {
             files: Vec::new(),
             fields: HashMap::new(),
         };
 
-        while let Ok(Some(mut field)) = multipart.next_field().await {
+        let mut parts = 0;
+        let mut text_len = 0;
+
+        while let Some(mut field) = multipart.next_field().await.map_err(multipart_error)? {
+            parts += 1;
+            if let Some(max) = config.max_parts
+                && parts > max
+            {
+                return Err(Box::new(HttpError::new(
+                    StatusCode::ContentTooLarge,
+                    format!("Multipart body exceeds the limit of {} parts", max),
+                )));
+            }
+
             let name = field.name().unwrap_or_default().to_string();
 
             if let Some(file_name) = field.file_name() {
-                let filename = file_name.to_string();
+                if let Some(max) = config.max_files
+                    && form_data.files.len() >= max
+                {
+                    return Err(Box::new(HttpError::new(
+                        StatusCode::ContentTooLarge,
+                        format!("Multipart body exceeds the limit of {} files", max),
+                    )));
+                }
+
+                let filename = Path::new(file_name)
+                    .file_name()
+                    .map(|n| n.to_string_lossy().into_owned())
+                    .unwrap_or_default();
                 let unique_id = Uuid::new_v4();
-                let temp_path =
-                    std::env::temp_dir().join(format!("bolt_upload_{}_{}", unique_id, filename));
+                let temp_path = config
+                    .temp_dir()
+                    .join(format!("bolt_upload_{}_{}", unique_id, filename));
 
-                let mut dest = tokio::fs::File::create(&temp_path).await?;
+                let mut options = tokio::fs::OpenOptions::new();
+                options.write(true).create_new(true);
+                #[cfg(unix)]
+                options.mode(self.config.upload_mode);
+
+                let mut dest = options.open(&temp_path).await?;
+                self.temp_paths.push(temp_path.display().to_string());
+
+                let mut written: u64 = 0;
+                while let Some(chunk) = field.chunk().await.map_err(multipart_error)? {
+                    written += chunk.len() as u64;
+                    if let Some(max) = config.max_file_size
+                        && written > max
+                    {
+                        return Err(Box::new(HttpError::new(
+                            StatusCode::ContentTooLarge,
+                            format!("Uploaded file exceeds the limit of {} bytes", max),
+                        )));
+                    }
 
-                while let Some(chunk) = field.chunk().await? {
                     dest.write_all(&chunk).await?;
                 }
 
-                self.temp_paths.push(temp_path.display().to_string());
-
                 form_data.files.push(FormFile {
                     field_name: name,
                     file_name: filename,
@@ -276,11 +772,24 @@ impl RequestBody {
                     temp_path: temp_path.display().to_string(),
                 });
             } else {
-                form_data.fields.insert(name, field.text().await?);
+                let mut text = Vec::new();
+                while let Some(chunk) = field.chunk().await.map_err(multipart_error)? {
+                    text_len += chunk.len();
+                    if let Some(max) = self.config.body_limit
+                        && text_len > max
+                    {
+                        return Err(body::too_large(max));
+                    }
+
+                    text.extend_from_slice(&chunk);
+                }
+
+                form_data
+                    .fields
+                    .insert(name, String::from_utf8_lossy(&text).into_owned());
             }
         }
 
-        self.form_data_result = Some(Ok(form_data.clone()));
         Ok(form_data)
     }
 
@@ -303,24 +812,104 @@ impl RequestBody {
     }
 }
 
+fn multipart_error(e: multer::Error) -> BoltError {
+    match e {
+        multer::Error::StreamReadFailed(inner) if inner.is::<HttpError>() => inner,
+        other => Box::new(HttpError::new(StatusCode::BadRequest, other.to_string())),
+    }
+}
+
+pub(crate) fn sweep_uploads(dir: &Path, ttl: Duration) {
+    let Ok(entries) = std::fs::read_dir(dir) else {
+        return;
+    };
+
+    for entry in entries.flatten() {
+        if !entry
+            .file_name()
+            .to_string_lossy()
+            .starts_with("bolt_upload_")
+        {
+            continue;
+        }
+
+        let expired = entry
+            .metadata()
+            .and_then(|m| m.modified())
+            .ok()
+            .and_then(|m| m.elapsed().ok())
+            .is_some_and(|age| age > ttl);
+
+        if expired {
+            let _ = std::fs::remove_file(entry.path());
+        }
+    }
+}
+
+pub(crate) fn parse_quality_list(header: &str) -> Vec<(String, f32)> {
+    let mut entries: Vec<(String, f32)> = quality_entries(header)
+        .into_iter()
+        .filter(|(_, q)| *q > 0.0)
+        .collect();
+
+    entries.sort_by(|a, b| b.1.total_cmp(&a.1));
+    entries
+}
+
+fn quality_entries(header: &str) -> Vec<(String, f32)> {
+    header
+        .split(',')
+        .filter_map(|item| {
+            let mut parts = item.split(';').map(|p| p.trim());
+            let value = parts.next()?.to_ascii_lowercase();
+            if value.is_empty() {
+                return None;
+            }
+
+            let mut q = 1.0;
+            for param in parts {
+                if let Some(v) = param.strip_prefix("q=") {
+                    q = v.parse().unwrap_or(0.0);
+                }
+            }
+
+            Some((value, q))
+        })
+        .collect()
+}
+
+fn media_match(ranges: &[(String, f32)], mime: &str) -> Option<(f32, u8, usize)> {
+    let mime = mime.split(';').next()?.trim().to_ascii_lowercase();
+    let (kind, _) = mime.split_once('/')?;
+
+    let mut best: Option<(f32, u8, usize)> = None;
+    for (index, (range, q)) in ranges.iter().enumerate() {
+        let specificity = if *range == mime {
+            2
+        } else if range.strip_suffix("/*") == Some(kind) {
+            1
+        } else if range == "*/*" {
+            0
+        } else {
+            continue;
+        };
+
+        if best.is_none_or(|(_, s, _)| specificity > s) {
+            best = Some((*q, specificity, index));
+        }
+    }
+
+    best
+}
+
 impl Drop for RequestBody {
     fn drop(&mut self) {
         if self.temp_paths.is_empty() {
             return;
         }
 
-        let paths = std::mem::take(&mut self.temp_paths);
-
-        if tokio::runtime::Handle::try_current().is_ok() {
-            tokio::spawn(async move {
-                for path in paths {
-                    let _ = tokio::fs::remove_file(&path).await;
-                }
-            });
-        } else {
-            for path in paths {
-                let _ = std::fs::remove_file(&path);
-            }
+        for path in self.temp_paths.drain(..) {
+            let _ = std::fs::remove_file(&path);
         }
     }
 }
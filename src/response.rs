@@ -1,56 +1,130 @@
-use base64::{Engine, engine::general_purpose};
 use bytes::Bytes;
-use cookie::{Cookie, SameSite};
-use http_body_util::Full;
+use cookie::{Cookie, CookieJar, Key};
+use futures_util::{Stream, StreamExt, TryStreamExt, stream};
+use http_body_util::{BodyExt, Full, StreamBody, combinators::UnsyncBoxBody};
 use hyper::{
     HeaderMap, Response,
-    header::{HeaderName, HeaderValue},
+    body::Frame,
+    ext::ReasonPhrase,
+    header::{CONTENT_LENGTH, HeaderName, HeaderValue},
 };
 use mime_guess::from_path;
 use serde::Serialize;
-use std::path::Path;
+use std::{
+    io,
+    io::SeekFrom,
+    path::Path,
+    time::{Duration, SystemTime},
+};
 use tokio::fs;
-use tokio::io::AsyncReadExt;
+use tokio::io::{AsyncReadExt, AsyncSeekExt};
+use tokio::sync::mpsc;
+use tokio_util::io::ReaderStream;
+
+use crate::{
+    file::{self, Precondition},
+    http::{CustomCode, ProblemDetails, StatusCode},
+    request::RequestBody,
+    types::{Method, TimeoutKind},
+};
+
+#[cfg(feature = "templates")]
+use {crate::templates::Templates, std::sync::Arc};
 
-use crate::http::StatusCode;
+pub use crate::file::{Asset, AssetSource, EmbeddedAssets, FileOptions};
+pub use crate::sse::SseEvent;
+pub use cookie::SameSite;
+
+pub type ResponseBody = UnsyncBoxBody<Bytes, io::Error>;
+
+const FALLBACK_ERROR_BODY: &str = r#"{"message":"Internal Server Error","status":500}"#;
 
 pub struct ResponseWriter {
-    pub body: String,
+    pub body: Bytes,
     pub headers: HeaderMap,
     pub status: StatusCode,
     pub has_error: bool,
+    pub completed: bool,
+    pub transformed: bool,
+    request_id: Option<String>,
+    timeout: Option<TimeoutKind>,
+    cookie_key: Option<Key>,
+    base_url: Option<url::Url>,
+    #[cfg(feature = "templates")]
+    templates: Option<Arc<Templates>>,
+    stream: Option<ResponseBody>,
 }
 
 #[allow(dead_code)]
 impl ResponseWriter {
     pub fn new() -> Self {
         Self {
-            body: "".into(),
+            body: Bytes::new(),
             headers: HeaderMap::new(),
             status: StatusCode::OK,
             has_error: false,
+            completed: false,
+            transformed: false,
+            request_id: None,
+            timeout: None,
+            cookie_key: None,
+            base_url: None,
+            #[cfg(feature = "templates")]
+            templates: None,
+            stream: None,
         }
     }
 
+    pub fn request_id(&self) -> Option<&str> {
+        self.request_id.as_deref()
+    }
+
+    pub fn set_request_id(&mut self, id: &str) {
+        self.request_id = Some(id.to_string());
+    }
+
     pub fn status(&mut self, status: StatusCode) -> &mut Self {
         self.status = status;
         self
     }
 
+    pub fn status_code(&mut self, code: u16) -> &mut Self {
+        self.status = StatusCode::from_u16(code)
+            .or_else(|| CustomCode::new(code).map(StatusCode::Custom))
+            .unwrap_or(StatusCode::InternalServerError);
+        self
+    }
+
     pub fn set_header(&mut self, key: &str, value: &str) -> &mut Self {
-        self.headers.insert(
-            HeaderName::from_bytes(key.as_bytes()).unwrap(),
-            HeaderValue::from_str(value).unwrap(),
-        );
+        match (
+            HeaderName::from_bytes(key.as_bytes()),
+            HeaderValue::from_str(value),
+        ) {
+            (Ok(key), Ok(value)) => {
+                self.headers.insert(key, value);
+            }
+            _ => tracing::warn!(header = key, "Skipping invalid response header"),
+        }
         self
     }
 
+    pub fn allow(&mut self, methods: &[Method]) -> &mut Self {
+        let value = methods
+            .iter()
+            .map(|m| m.as_str())
+            .collect::<Vec<_>>()
+            .join(", ");
+
+        self.set_header("Allow", &value)
+    }
+
     pub fn get_header(&self, key: &str) -> Option<&HeaderValue> {
         self.headers.get(key)
     }
 
     pub fn send(&mut self, body: &str) -> &mut Self {
-        self.body = body.into();
+        self.body = Bytes::copy_from_slice(body.as_bytes());
+        self.stream = None;
         self
     }
 
@@ -58,112 +132,459 @@ impl ResponseWriter {
         match serde_json::to_string(data) {
             Ok(body) => {
                 self.set_header("Content-Type", "application/json");
-                self.body = body;
+                self.body = Bytes::from(body);
+                self.stream = None;
             }
             Err(_) => {
-                self.set_header("Content-Type", "application/json");
-                self.body = r#"{"error":"Failed to serialize JSON"}"#.to_string();
-                self.status = StatusCode::InternalServerError;
+                self.fallback_error();
+            }
+        }
+        self
+    }
+
+    pub fn problem(&mut self, problem: &ProblemDetails) -> &mut Self {
+        match serde_json::to_string(problem) {
+            Ok(body) => {
+                self.status_code(problem.status)
+                    .set_header("Content-Type", "application/problem+json");
+                self.body = Bytes::from(body);
+                self.stream = None;
+            }
+            Err(_) => {
+                self.fallback_error();
             }
         }
         self
     }
 
+    pub fn fallback_error(&mut self) -> &mut Self {
+        self.headers.insert(
+            hyper::header::CONTENT_TYPE,
+            HeaderValue::from_static("application/json"),
+        );
+        self.status = StatusCode::InternalServerError;
+        self.body = Bytes::from_static(FALLBACK_ERROR_BODY.as_bytes());
+        self.stream = None;
+        self
+    }
+
+    pub fn redirect(&mut self, location: &str, status: StatusCode) -> &mut Self {
+        let code = self.get_code(status);
+        let status = if (300..400).contains(&code) {
+            status
+        } else {
+            StatusCode::Found
+        };
+
+        let resolved = match &self.base_url {
+            Some(base) if url::Url::parse(location).is_err() => base.join(location).ok(),
+            _ => None,
+        };
+        let location = resolved.as_ref().map_or(location, |url| url.as_str());
+
+        match HeaderValue::from_str(location) {
+            Ok(value) => {
+                self.headers.insert(hyper::header::LOCATION, value);
+                self.status = status;
+                self.body = Bytes::new();
+                self.stream = None;
+            }
+            Err(_) => {
+                self.error(StatusCode::InternalServerError, "Invalid redirect location");
+            }
+        }
+
+        self
+    }
+
+    pub fn redirect_temporary(&mut self, location: &str) -> &mut Self {
+        self.redirect(location, StatusCode::TemporaryRedirect)
+    }
+
+    pub fn redirect_permanent(&mut self, location: &str) -> &mut Self {
+        self.redirect(location, StatusCode::PermanentRedirect)
+    }
+
+    pub fn text(&mut self, body: &str) -> &mut Self {
+        self.set_header("Content-Type", "text/plain; charset=utf-8");
+        self.body = Bytes::copy_from_slice(body.as_bytes());
+        self.stream = None;
+        self
+    }
+
+    #[cfg(feature = "templates")]
+    pub(crate) fn set_templates(&mut self, templates: Option<Arc<Templates>>) {
+        self.templates = templates;
+    }
+
+    #[cfg(feature = "templates")]
+    pub fn render<T: Serialize>(&mut self, name: &str, context: &T) -> &mut Self {
+        let Some(templates) = self.templates.clone() else {
+            return self.error(
+                StatusCode::InternalServerError,
+                "No template directory configured",
+            );
+        };
+
+        match templates.render(name, context) {
+            Ok(html) => self.html(&html),
+            Err(e) => self.error(StatusCode::InternalServerError, &e),
+        }
+    }
+
     pub fn html(&mut self, html: &str) -> &mut Self {
         self.set_header("Content-Type", "text/html; charset=utf-8");
-        self.body = html.to_string();
+        self.body = Bytes::copy_from_slice(html.as_bytes());
+        self.stream = None;
         self
     }
 
     pub async fn file<P: AsRef<Path>>(&mut self, path: P) {
-        let path_ref = path.as_ref();
-
-        match fs::File::open(path_ref).await {
-            Ok(mut file) => {
-                let mut buf = Vec::new();
-                if let Err(e) = file.read_to_end(&mut buf).await {
-                    self.error(
-                        StatusCode::InternalServerError,
-                        &format!("Failed to read file: {}", e),
-                    );
-                    return;
-                }
+        self.send_file(None, path.as_ref(), &FileOptions::default())
+            .await;
+    }
 
-                let mime_type = from_path(path_ref).first_or_octet_stream().to_string();
+    pub async fn serve_file<P: AsRef<Path>>(
+        &mut self,
+        req: &RequestBody,
+        path: P,
+        opts: FileOptions,
+    ) {
+        self.send_file(Some(req), path.as_ref(), &opts).await;
+    }
 
-                self.status(StatusCode::OK)
-                    .set_header("Content-Type", &mime_type)
-                    .bytes(&buf);
-            }
+    pub fn serve_asset<A: AssetSource + ?Sized>(
+        &mut self,
+        req: &RequestBody,
+        source: &A,
+        path: &str,
+        opts: FileOptions,
+    ) {
+        let Some(asset) = source.get(path) else {
+            self.error(StatusCode::NotFound, "File not found");
+            return;
+        };
+
+        let len = asset.data.len() as u64;
+        let etag = opts.etag.then(|| file::content_etag(&asset.data));
+        let mime_type = from_path(path).first_or_octet_stream().to_string();
+
+        self.file_headers(
+            &mime_type,
+            etag.as_deref(),
+            asset.modified,
+            &opts,
+            opts.ranges,
+        );
+
+        if !self.check_preconditions(req, etag.as_deref(), asset.modified) {
+            return;
+        }
+
+        let Some((start, end)) =
+            self.byte_range(req, etag.as_deref(), asset.modified, len, opts.ranges)
+        else {
+            return;
+        };
+
+        self.set_header("Content-Length", &(end - start).to_string());
+        if req.method() != hyper::Method::HEAD {
+            self.body = asset.data.slice(start as usize..end as usize);
+            self.stream = None;
+        }
+    }
+
+    async fn send_file(&mut self, req: Option<&RequestBody>, path: &Path, opts: &FileOptions) {
+        let mut file = match fs::File::open(path).await {
+            Ok(file) => file,
             Err(_) => {
                 self.error(StatusCode::NotFound, "File not found");
+                return;
+            }
+        };
+
+        let meta = match file.metadata().await {
+            Ok(meta) if meta.is_file() => meta,
+            _ => {
+                self.error(StatusCode::NotFound, "File not found");
+                return;
+            }
+        };
+
+        let len = meta.len();
+        let modified = meta.modified().ok();
+        let mut etag = modified.filter(|_| opts.etag).map(|m| file::etag(len, m));
+
+        let mime_type = from_path(path).first_or_octet_stream().to_string();
+
+        let compressible = opts.compress && file::compressible(&mime_type, len);
+        let encoding = req.filter(|_| compressible).and_then(file::negotiate);
+
+        if let Some(encoding) = encoding {
+            etag = etag.map(|tag| file::encoded_etag(&tag, encoding));
+        }
+
+        let ranges = req.is_some() && opts.ranges && encoding.is_none();
+        self.file_headers(&mime_type, etag.as_deref(), modified, opts, ranges);
+
+        if compressible {
+            self.set_header("Vary", "Accept-Encoding");
+        }
+
+        let (mut start, mut end) = (0, len);
+
+        if let Some(req) = req {
+            if !self.check_preconditions(req, etag.as_deref(), modified) {
+                return;
+            }
+
+            if let Some(encoding) = encoding {
+                let body = match file::compressed(path, encoding, modified, &mut file).await {
+                    Ok(body) => body,
+                    Err(e) => {
+                        self.error(
+                            StatusCode::InternalServerError,
+                            &format!("Failed to read file: {}", e),
+                        );
+                        return;
+                    }
+                };
+
+                self.set_header("Content-Encoding", encoding.as_str())
+                    .set_header("Content-Length", &body.len().to_string())
+                    .mark_transformed();
+
+                if req.method() != hyper::Method::HEAD {
+                    self.body = body;
+                    self.stream = None;
+                }
+                return;
+            }
+
+            match self.byte_range(req, etag.as_deref(), modified, len, ranges) {
+                Some(range) => (start, end) = range,
+                None => return,
+            }
+
+            if req.method() == hyper::Method::HEAD {
+                self.set_header("Content-Length", &(end - start).to_string())
+                    .send("");
+                return;
+            }
+        }
+
+        if start > 0
+            && let Err(e) = file.seek(SeekFrom::Start(start)).await
+        {
+            self.error(
+                StatusCode::InternalServerError,
+                &format!("Failed to read file: {}", e),
+            );
+            return;
+        }
+
+        self.set_header("Content-Length", &(end - start).to_string());
+        self.set_stream(ReaderStream::new(file.take(end - start)));
+    }
+
+    fn file_headers(
+        &mut self,
+        mime_type: &str,
+        etag: Option<&str>,
+        modified: Option<SystemTime>,
+        opts: &FileOptions,
+        ranges: bool,
+    ) {
+        self.status(StatusCode::OK)
+            .set_header("Content-Type", mime_type);
+
+        if let Some(tag) = etag {
+            self.set_header("ETag", tag);
+        }
+
+        if let Some(m) = modified.filter(|_| opts.last_modified) {
+            self.set_header("Last-Modified", &httpdate::fmt_http_date(m));
+        }
+
+        if let Some(cache_control) = &opts.cache_control {
+            self.set_header("Cache-Control", cache_control);
+        }
+
+        if ranges {
+            self.set_header("Accept-Ranges", "bytes");
+        }
+    }
+
+    fn check_preconditions(
+        &mut self,
+        req: &RequestBody,
+        etag: Option<&str>,
+        modified: Option<SystemTime>,
+    ) -> bool {
+        match file::evaluate(req, etag, modified) {
+            Precondition::Proceed => true,
+            Precondition::NotModified => {
+                self.status(StatusCode::NotModified).send("");
+                self.strip_header("Content-Type");
+                false
+            }
+            Precondition::Failed => {
+                self.error(StatusCode::PreconditionFailed, "Precondition Failed");
+                false
             }
         }
     }
 
+    fn byte_range(
+        &mut self,
+        req: &RequestBody,
+        etag: Option<&str>,
+        modified: Option<SystemTime>,
+        len: u64,
+        ranges: bool,
+    ) -> Option<(u64, u64)> {
+        if !ranges {
+            return Some((0, len));
+        }
+
+        match file::range(req, etag, modified, len) {
+            Some(Ok((from, to))) if from < to => {
+                self.status(StatusCode::PartialContent).set_header(
+                    "Content-Range",
+                    &format!("bytes {}-{}/{}", from, to - 1, len),
+                );
+                Some((from, to))
+            }
+            Some(_) => {
+                self.set_header("Content-Range", &format!("bytes */{}", len));
+                self.error(StatusCode::RangeNotSatisfiable, "Range Not Satisfiable");
+                None
+            }
+            None => Some((0, len)),
+        }
+    }
+
+    pub fn set_stream<S>(&mut self, stream: S) -> &mut Self
+    where
+        S: Stream<Item = io::Result<Bytes>> + Send + 'static,
+    {
+        self.body = Bytes::new();
+        self.stream = Some(StreamBody::new(stream.map_ok(Frame::data)).boxed_unsync());
+        self
+    }
+
+    pub fn stream_channel(&mut self) -> mpsc::Sender<Bytes> {
+        let (tx, rx) = mpsc::channel(16);
+
+        self.set_stream(stream::unfold(rx, |mut rx| async move {
+            rx.recv().await.map(|chunk| (Ok(chunk), rx))
+        }));
+
+        tx
+    }
+
+    pub fn sse<S>(&mut self, events: S) -> &mut Self
+    where
+        S: Stream<Item = SseEvent> + Send + 'static,
+    {
+        self.sse_with_keep_alive(events, Duration::from_secs(15))
+    }
+
+    pub fn sse_with_keep_alive<S>(&mut self, events: S, keep_alive: Duration) -> &mut Self
+    where
+        S: Stream<Item = SseEvent> + Send + 'static,
+    {
+        let start = tokio::time::Instant::now() + keep_alive;
+        let ticker = tokio::time::interval_at(start, keep_alive);
+
+        let stream = stream::unfold(
+            (Box::pin(events), ticker),
+            |(mut events, mut ticker)| async move {
+                let chunk = tokio::select! {
+                    event = events.next() => event?.to_bytes(),
+                    _ = ticker.tick() => Bytes::from_static(b":\n\n"),
+                };
+
+                ticker.reset();
+                Some((Ok(chunk), (events, ticker)))
+            },
+        );
+
+        self.set_header("Content-Type", "text/event-stream")
+            .set_header("Cache-Control", "no-cache")
+            .set_stream(stream)
+    }
+
+    pub fn is_streaming(&self) -> bool {
+        self.stream.is_some()
+    }
+
     pub fn bytes(&mut self, bytes: &[u8]) -> &mut Self {
-        let encoded = general_purpose::STANDARD.encode(bytes);
-        self.body = encoded;
+        self.body = Bytes::copy_from_slice(bytes);
+        self.stream = None;
         self.set_header("Content-Type", "application/octet-stream");
         self
     }
 
     pub fn get_code(&self, code: StatusCode) -> u16 {
-        match code {
-            StatusCode::Continue => return 100,
-            StatusCode::SwitchingProtocols => return 101,
-            StatusCode::Processing => return 102,
-            StatusCode::EarlyHints => return 103,
-            StatusCode::OK => return 200,
-            StatusCode::Created => return 201,
-            StatusCode::Accepted => return 202,
-            StatusCode::NonAuthoritativeInformation => return 203,
-            StatusCode::NoContent => return 204,
-            StatusCode::ResetContent => return 205,
-            StatusCode::PartialContent => return 206,
-            StatusCode::MovedPermanently => return 301,
-            StatusCode::Found => return 302,
-            StatusCode::SeeOther => return 303,
-            StatusCode::NotModified => return 304,
-            StatusCode::TemporaryRedirect => return 307,
-            StatusCode::PermanentRedirect => return 308,
-            StatusCode::BadRequest => return 400,
-            StatusCode::Unauthorized => return 401,
-            StatusCode::PaymentRequired => return 402,
-            StatusCode::Forbidden => return 403,
-            StatusCode::NotFound => return 404,
-            StatusCode::MethodNotAllowed => return 405,
-            StatusCode::NotAcceptable => return 406,
-            StatusCode::ProxyAuthenticationRequired => return 407,
-            StatusCode::RequestTimeout => return 408,
-            StatusCode::Conflict => return 409,
-            StatusCode::Gone => return 410,
-            StatusCode::LengthRequired => return 411,
-            StatusCode::PreconditionFailed => return 412,
-            StatusCode::ContentTooLarge => return 413,
-            StatusCode::URITooLong => return 414,
-            StatusCode::UnsupportedMediaType => return 415,
-            StatusCode::TooManyRequests => return 429,
-            StatusCode::InternalServerError => return 500,
-            StatusCode::NotImplemented => return 501,
-            StatusCode::BadGateway => return 502,
-            StatusCode::ServiceUnavailable => return 503,
-            StatusCode::GatewayTimeout => return 504,
-            StatusCode::HTTPVersionNotSupported => return 505,
-        }
+        code.as_u16()
     }
 
     pub fn error(&mut self, status: StatusCode, msg: &str) -> &mut Self {
         self.status = status;
-        self.body = msg.to_string();
+        self.body = Bytes::copy_from_slice(msg.as_bytes());
+        self.stream = None;
         self.has_error = true;
         self
     }
 
+    pub(crate) fn timed_out(&mut self, kind: TimeoutKind, msg: &str) -> &mut Self {
+        let status = match kind {
+            TimeoutKind::Body => StatusCode::RequestTimeout,
+            TimeoutKind::Upstream | TimeoutKind::Handler => StatusCode::GatewayTimeout,
+        };
+
+        self.timeout = Some(kind);
+        self.error(status, msg)
+    }
+
+    pub fn timeout_kind(&self) -> Option<TimeoutKind> {
+        self.timeout
+    }
+
     pub fn has_error(&self) -> bool {
         self.has_error
     }
 
+    pub fn complete(&mut self) -> &mut Self {
+        self.completed = true;
+        self
+    }
+
+    pub fn is_complete(&self) -> bool {
+        self.completed
+    }
+
+    pub fn mark_transformed(&mut self) -> &mut Self {
+        self.transformed = true;
+        self
+    }
+
+    pub fn is_transformed(&self) -> bool {
+        self.transformed
+    }
+
+    pub fn body_len(&self) -> Option<usize> {
+        if !self.is_streaming() {
+            return Some(self.body.len());
+        }
+
+        self.headers
+            .get(hyper::header::CONTENT_LENGTH)
+            .and_then(|v| v.to_str().ok())
+            .and_then(|v| v.parse().ok())
+    }
+
     pub fn cookie(
         &mut self,
         name: &str,
@@ -175,50 +596,121 @@ impl ResponseWriter {
         http_only: bool,
         same_site: Option<&str>,
     ) -> &mut Self {
-        let mut cookie_builder = Cookie::build((name, value))
-            .path(path.unwrap_or("/"))
-            .secure(secure)
-            .http_only(http_only);
+        let mut builder = self.set_cookie(name, value).path(path.unwrap_or("/"));
+
+        if secure {
+            builder = builder.secure();
+        }
+
+        if http_only {
+            builder = builder.http_only();
+        }
 
         if let Some(d) = domain {
-            cookie_builder = cookie_builder.domain(d);
+            builder = builder.domain(d);
         }
 
         if let Some(age) = max_age {
-            cookie_builder = cookie_builder.max_age(time::Duration::seconds(age));
+            builder = builder.max_age(Duration::from_secs(age.max(0) as u64));
         }
 
-        if let Some(ss) = same_site {
-            match ss.to_lowercase().as_str() {
-                "lax" => cookie_builder = cookie_builder.same_site(SameSite::Lax),
-                "strict" => cookie_builder = cookie_builder.same_site(SameSite::Strict),
-                "none" => cookie_builder = cookie_builder.same_site(SameSite::None).secure(true),
-                _ => {}
-            }
+        match same_site.map(|ss| ss.to_ascii_lowercase()).as_deref() {
+            Some("lax") => builder = builder.same_site(SameSite::Lax),
+            Some("strict") => builder = builder.same_site(SameSite::Strict),
+            Some("none") => builder = builder.same_site(SameSite::None),
+            _ => {}
         }
 
-        self.headers.append(
-            hyper::header::SET_COOKIE,
-            hyper::header::HeaderValue::from_str(&cookie_builder.to_string()).unwrap(),
-        );
+        builder.finish()
+    }
 
-        self
+    pub fn set_cookie(&mut self, name: &str, value: &str) -> CookieBuilder<'_> {
+        CookieBuilder {
+            cookie: Cookie::build((name.to_string(), value.to_string())).path("/"),
+            protection: Protection::Plain,
+            res: self,
+        }
+    }
+
+    pub(crate) fn set_base_url(&mut self, base: Option<url::Url>) {
+        self.base_url = base;
+    }
+
+    pub(crate) fn set_cookie_key(&mut self, key: Option<Key>) {
+        self.cookie_key = key;
     }
 
-    pub fn into_response(&self) -> Response<Full<Bytes>> {
-        let status = &self.status;
+    pub fn signed_cookie(&mut self, cookie: impl Into<Cookie<'static>>) -> &mut Self {
+        self.secure_cookie(cookie.into(), false)
+    }
+
+    pub fn encrypted_cookie(&mut self, cookie: impl Into<Cookie<'static>>) -> &mut Self {
+        self.secure_cookie(cookie.into(), true)
+    }
+
+    fn secure_cookie(&mut self, cookie: Cookie<'static>, private: bool) -> &mut Self {
+        let Some(key) = self.cookie_key.clone() else {
+            return self.error(StatusCode::InternalServerError, "No cookie key configured");
+        };
 
-        let status_code = self.get_code(status.clone());
-        let body = &self.body;
+        let mut jar = CookieJar::new();
+
+        if private {
+            jar.private_mut(&key).add(cookie);
+        } else {
+            jar.signed_mut(&key).add(cookie);
+        }
+
+        for cookie in jar.delta() {
+            if let Ok(value) = HeaderValue::from_str(&cookie.to_string()) {
+                self.headers.append(hyper::header::SET_COOKIE, value);
+            }
+        }
+
+        self
+    }
+
+    pub fn into_response(self) -> Response<ResponseBody> {
+        let status_code = self.get_code(self.status);
         let mut builder = Response::builder().status(status_code);
 
-        for (key, value) in self.headers.iter() {
-            builder = builder.header(key, value);
+        let reason = self.status.reason_phrase();
+        if !reason.is_empty() {
+            builder = builder.extension(ReasonPhrase::from_static(reason.as_bytes()));
         }
 
-        builder
-            .body(Full::new(Bytes::from(body.to_owned())))
-            .unwrap()
+        let mut headers = self.headers;
+
+        if self.stream.is_none() {
+            let bodiless = status_code < 200 || matches!(status_code, 204 | 304);
+
+            if bodiless {
+                headers.remove(CONTENT_LENGTH);
+            } else if !self.body.is_empty() || !headers.contains_key(CONTENT_LENGTH) {
+                headers.insert(CONTENT_LENGTH, HeaderValue::from(self.body.len()));
+            }
+        }
+
+        if let Some(map) = builder.headers_mut() {
+            *map = headers;
+        }
+
+        let body = match self.stream {
+            Some(stream) => stream,
+            None => Full::new(self.body)
+                .map_err(|never| match never {})
+                .boxed_unsync(),
+        };
+
+        builder.body(body).unwrap_or_else(|_| {
+            let mut res = Response::new(
+                Full::new(Bytes::new())
+                    .map_err(|never| match never {})
+                    .boxed_unsync(),
+            );
+            *res.status_mut() = hyper::StatusCode::INTERNAL_SERVER_ERROR;
+            res
+        })
     }
 
     pub fn strip_header(&mut self, key: &str) {
@@ -227,3 +719,77 @@ impl ResponseWriter {
         }
     }
 }
+
+enum Protection {
+    Plain,
+    Signed,
+    Encrypted,
+}
+
+pub struct CookieBuilder<'a> {
+    res: &'a mut ResponseWriter,
+    cookie: cookie::CookieBuilder<'static>,
+    protection: Protection,
+}
+
+impl<'a> CookieBuilder<'a> {
+    pub fn max_age(mut self, max_age: Duration) -> Self {
+        let secs = i64::try_from(max_age.as_secs()).unwrap_or(i64::MAX);
+        self.cookie = self.cookie.max_age(time::Duration::seconds(secs));
+        self
+    }
+
+    pub fn path(mut self, path: &str) -> Self {
+        self.cookie = self.cookie.path(path.to_string());
+        self
+    }
+
+    pub fn domain(mut self, domain: &str) -> Self {
+        self.cookie = self.cookie.domain(domain.to_string());
+        self
+    }
+
+    pub fn secure(mut self) -> Self {
+        self.cookie = self.cookie.secure(true);
+        self
+    }
+
+    pub fn http_only(mut self) -> Self {
+        self.cookie = self.cookie.http_only(true);
+        self
+    }
+
+    pub fn same_site(mut self, same_site: SameSite) -> Self {
+        self.cookie = self.cookie.same_site(same_site);
+        self
+    }
+
+    pub fn signed(mut self) -> Self {
+        self.protection = Protection::Signed;
+        self
+    }
+
+    pub fn encrypted(mut self) -> Self {
+        self.protection = Protection::Encrypted;
+        self
+    }
+
+    pub fn finish(self) -> &'a mut ResponseWriter {
+        let mut cookie = self.cookie.build();
+
+        if cookie.same_site() == Some(SameSite::None) {
+            cookie.set_secure(true);
+        }
+
+        match self.protection {
+            Protection::Plain => {
+                if let Ok(value) = HeaderValue::from_str(&cookie.to_string()) {
+                    self.res.headers.append(hyper::header::SET_COOKIE, value);
+                }
+                self.res
+            }
+            Protection::Signed => self.res.signed_cookie(cookie),
+            Protection::Encrypted => self.res.encrypted_cookie(cookie),
+        }
+    }
+}
@@ -1,112 +1,247 @@
-use radixmap::RadixMap;
+use percent_encoding::percent_decode_str;
 
 use crate::types::{Handler, Method, Middleware};
-use std::{collections::HashMap, sync::Arc};
+use std::{cmp::Reverse, collections::HashMap, sync::Arc};
+use tracing::warn;
+
+type Scoped = (usize, Arc<dyn Middleware>);
+
+/// A matched handler with its captured params and the route it was registered under.
+type Found<'a> = (&'a Arc<dyn Handler>, HashMap<String, String>, &'a str);
+
+/// Static segments, then `:param` segments, then whether the route has no wildcard.
+type Rank = (usize, usize, bool);
 
 #[derive(Clone)]
 struct Node {
+    route: String,
     pub handlers: HashMap<Method, Arc<dyn Handler>>,
-    middleware: HashMap<Method, Vec<Arc<dyn Middleware>>>,
+    middleware: HashMap<Method, Vec<Scoped>>,
 }
 
 impl Node {
-    pub fn new() -> Self {
+    pub fn new(route: &str) -> Self {
         Self {
+            route: route.to_string(),
             handlers: HashMap::new(),
             middleware: HashMap::new(),
         }
     }
+
+    fn handler(&self, method: Method) -> Option<&Arc<dyn Handler>> {
+        self.handlers.get(&method).or_else(|| match method {
+            Method::HEAD => self.handlers.get(&Method::GET),
+            _ => None,
+        })
+    }
+
+    fn methods(&self) -> impl Iterator<Item = Method> + '_ {
+        Method::ALL
+            .into_iter()
+            .filter(|m| self.handler(*m).is_some())
+    }
 }
 
 #[derive(Clone)]
 pub struct Router {
-    router: RadixMap<Node>,
+    router: HashMap<String, Node>,
+    dynamic: HashMap<String, Vec<(String, Rank, usize)>>,
+    scopes: HashMap<String, Vec<String>>,
+    dynamic_scopes: Vec<String>,
+    sequence: usize,
+    hosts: HashMap<String, Router>,
 }
 
 impl Router {
     pub fn new() -> Self {
         Self {
-            router: RadixMap::new(),
+            router: HashMap::new(),
+            dynamic: HashMap::new(),
+            scopes: HashMap::new(),
+            dynamic_scopes: Vec::new(),
+            sequence: 0,
+            hosts: HashMap::new(),
         }
     }
 
+    pub fn host_mut(&mut self, host: &str) -> &mut Router {
+        self.hosts
+            .entry(host.to_ascii_lowercase())
+            .or_insert_with(Router::new)
+    }
+
+    pub fn for_host(&self, host: &str) -> Option<&Router> {
+        if self.hosts.is_empty() {
+            return None;
+        }
+
+        let host = strip_port(host).to_ascii_lowercase();
+
+        self.hosts.get(&host).or_else(|| {
+            self.hosts
+                .iter()
+                .filter_map(|(pattern, router)| {
+                    let suffix = pattern.strip_prefix('*')?;
+                    (suffix.starts_with('.') && host.ends_with(suffix)).then_some((suffix, router))
+                })
+                .max_by_key(|(suffix, _)| suffix.len())
+                .map(|(_, router)| router)
+        })
+    }
+
     pub fn insert<H>(&mut self, path: &str, method: Method, handler: H)
     where
         H: Handler + 'static,
     {
-        let key = path.as_bytes();
+        let path = &normalize(path);
 
-        if let Some(node) = self.router.get_mut(key) {
-            node.handlers.insert(method, Arc::new(handler));
+        if let Some((other, ..)) = self.dynamic.values().flatten().find(|(route, ..)| {
+            route != path
+                && shape(route) == shape(path)
+                && self
+                    .router
+                    .get(route)
+                    .is_some_and(|node| node.handlers.contains_key(&method))
+        }) {
+            warn!(
+                "{} {} has the same shape as {}; only one of them can ever match",
+                method, path, other
+            );
+        }
+
+        if let Some(node) = self.router.get_mut(path) {
+            if node.handlers.insert(method, Arc::new(handler)).is_some() {
+                warn!(
+                    "{} {} registered twice; the later handler replaces the earlier one",
+                    method, path
+                );
+            }
         } else {
-            let mut node = Node::new();
+            let mut node = Node::new(path);
             node.handlers.insert(method, Arc::new(handler));
-            let _ = self.router.insert(key.to_vec(), node);
+            self.router.insert(path.clone(), node);
+        }
+
+        if is_dynamic(path) {
+            let seq = self.sequence;
+            let routes = self.dynamic.entry(bucket(path)).or_default();
+            if !routes.iter().any(|(route, ..)| route == path) {
+                self.sequence += 1;
+                routes.push((path.to_string(), rank(path), seq));
+                routes.sort_by(|a, b| b.1.cmp(&a.1).then_with(|| a.2.cmp(&b.2)));
+            }
         }
     }
 
     pub fn insert_middleware(&mut self, path: &str, method: Method, mw: Arc<dyn Middleware>) {
-        let key = path.as_bytes();
+        let path = &normalize(path);
+        let seq = self.sequence;
+        self.sequence += 1;
+
+        if let Some(node) = self.router.get_mut(path) {
+            node.middleware.entry(method).or_default().push((seq, mw));
+        } else {
+            let mut node = Node::new(path);
+            node.middleware.insert(method, vec![(seq, mw)]);
+            self.router.insert(path.clone(), node);
+        }
 
-        if let Some(node) = self.router.get_mut(key) {
-            node.middleware.entry(method).or_default().push(mw);
+        let scopes = if is_dynamic(path) {
+            &mut self.dynamic_scopes
         } else {
-            let mut node = Node::new();
-            node.middleware.insert(method, vec![mw]);
-            let _ = self.router.insert(key.to_vec(), node);
+            let prefix = path.trim_end_matches('/').to_string();
+            self.scopes.entry(prefix).or_default()
+        };
+        if !scopes.iter().any(|scope| scope == path) {
+            scopes.push(path.to_string());
         }
     }
 
     pub fn collect_middleware(&self, path: &str, method: Method) -> Vec<Arc<dyn Middleware>> {
-        let mut entries = vec![];
+        let mut chain = vec![];
 
-        for (key_bytes, node) in self.router.iter() {
-            let route = std::str::from_utf8(key_bytes).unwrap();
+        // Static scopes are found by looking up each segment prefix of the path;
+        // only the few parameterized scopes need to be matched one by one.
+        let inner = path.trim_end_matches('/');
+        let prefixes = std::iter::once("")
+            .chain(inner.match_indices('/').skip(1).map(|(i, _)| &inner[..i]))
+            .chain(std::iter::once(inner).filter(|p| !p.is_empty()));
 
-            if path.starts_with(route) {
-                if let Some(mws) = node.middleware.get(&method) {
-                    entries.push((route.to_string(), mws.clone()));
-                }
-            }
-        }
+        let scopes = prefixes
+            .filter_map(|prefix| self.scopes.get(prefix))
+            .flatten()
+            .chain(
+                self.dynamic_scopes
+                    .iter()
+                    .filter(|scope| scope_matches(scope, path)),
+            );
 
-        entries.sort_by_key(|(route, _)| route.len());
+        // HEAD is served by GET handlers, so it must pass GET's middleware as well.
+        let methods: &[Method] = match method {
+            Method::HEAD => &[Method::HEAD, Method::GET],
+            _ => &[method],
+        };
 
-        let mut final_list = vec![];
-        for (_, mws) in entries {
-            for mw in mws {
-                final_list.push(mw);
+        for scope in scopes {
+            let Some(node) = self.router.get(scope) else {
+                continue;
+            };
+
+            for mws in methods.iter().filter_map(|m| node.middleware.get(m)) {
+                chain.extend(mws.iter().map(|(seq, mw)| (scope.len(), *seq, mw.clone())));
             }
         }
 
-        final_list
+        chain.sort_by_key(|(len, seq, _)| (*len, *seq));
+
+        let mut seen = Vec::with_capacity(chain.len());
+        chain
+            .into_iter()
+            .map(|(_, _, mw)| mw)
+            .filter(|mw| {
+                let ptr = Arc::as_ptr(mw) as *const ();
+                let fresh = !seen.contains(&ptr);
+                seen.push(ptr);
+                fresh
+            })
+            .collect()
     }
 
     pub fn match_path(&self, route: &str, uri: &str) -> Option<HashMap<String, String>> {
+        let uri = uri.trim_matches('/');
         let route_segments: Vec<&str> = route.trim_matches('/').split('/').collect();
-        let uri_segments: Vec<&str> = uri.trim_matches('/').split('/').collect();
+        let uri_segments: Vec<&str> = uri.split('/').collect();
 
         let mut params = HashMap::new();
 
         for (i, route_seg) in route_segments.iter().enumerate() {
             if *route_seg == "*" {
                 return Some(params);
-            } else if route_seg.ends_with('*') {
+            } else if route_seg.starts_with('*') || route_seg.ends_with('*') {
                 let key = route_seg
                     .trim_start_matches(':')
-                    .trim_end_matches('*')
+                    .trim_matches('*')
                     .to_string();
 
-                let joined = uri_segments[i..].join("/");
+                let rest = uri.splitn(i + 1, '/').nth(i).unwrap_or_default();
 
-                params.insert(key, joined);
+                params.insert(key, rest.to_string());
 
                 return Some(params);
             } else if let Some(uri_seg) = uri_segments.get(i) {
                 if route_seg.starts_with(':') {
+                    if uri_seg.is_empty() {
+                        return None;
+                    }
+
                     let key = route_seg.trim_start_matches(':').to_string();
 
-                    params.insert(key, (*uri_seg).to_string());
+                    let value = percent_decode_str(uri_seg)
+                        .decode_utf8()
+                        .map(|v| v.into_owned())
+                        .unwrap_or_else(|_| (*uri_seg).to_string());
+
+                    params.insert(key, value);
                 } else if route_seg != uri_seg {
                     return None;
                 }
@@ -122,41 +257,224 @@ impl Router {
         }
     }
 
-    pub fn find(
-        &self,
-        path: &str,
-        method: Method,
-    ) -> Option<(&Arc<dyn Handler>, HashMap<String, String>)> {
-        let mut best_match: Option<(&Arc<dyn Handler>, HashMap<String, String>, usize)> = None;
-
-        for (key, node) in self.router.iter() {
-            let route = std::str::from_utf8(key).unwrap();
-
-            if let Some(params) = self.match_path(route, path) {
-                if let Some(handler) = node.handlers.get(&method) {
-                    let score = route
-                        .split('/')
-                        .filter(|s| !s.is_empty())
-                        .map(|s| {
-                            if s.starts_with(':') || s.ends_with('*') || s == "*" {
-                                0
-                            } else {
-                                1
-                            }
-                        })
-                        .sum();
-
-                    if best_match.is_none() {
-                        best_match = Some((handler, params, score));
-                    } else {
-                        if score > best_match.as_ref().unwrap().2 {
-                            best_match = Some((handler, params, score));
-                        }
-                    }
+    pub fn routes(&self) -> Vec<(Method, String)> {
+        let mut routes: Vec<(Method, String)> = self
+            .router
+            .values()
+            .flat_map(|node| {
+                Method::ALL
+                    .into_iter()
+                    .filter(|m| node.handlers.contains_key(m))
+                    .map(|m| (m, node.route.clone()))
+            })
+            .collect();
+
+        for (host, router) in &self.hosts {
+            routes.extend(
+                router
+                    .routes()
+                    .into_iter()
+                    .map(|(m, path)| (m, format!("{}{}", host, path))),
+            );
+        }
+
+        routes.sort_by(|a, b| a.1.cmp(&b.1).then_with(|| a.0.as_str().cmp(b.0.as_str())));
+        routes
+    }
+
+    pub fn methods_for(&self, route: &str) -> Vec<Method> {
+        match self.router.get(route) {
+            Some(node) => node.methods().collect(),
+            None => Vec::new(),
+        }
+    }
+
+    pub fn allowed_methods(&self, path: &str) -> Vec<Method> {
+        let statics = static_candidates(path)
+            .into_iter()
+            .filter_map(|candidate| self.router.get(&candidate))
+            .filter(|node| !is_dynamic(&node.route));
+
+        let dynamics = self
+            .buckets(path)
+            .flatten()
+            .filter(|(route, ..)| self.match_path(route, path).is_some())
+            .filter_map(|(route, ..)| self.router.get(route));
+
+        let allowed: Vec<Method> = statics.chain(dynamics).flat_map(Node::methods).collect();
+
+        Method::ALL
+            .into_iter()
+            .filter(|m| allowed.contains(m))
+            .collect()
+    }
+
+    pub fn find(&self, path: &str, method: Method) -> Option<Found<'_>> {
+        for candidate in &static_candidates(path) {
+            if let Some(node) = self.router.get(candidate)
+                && !is_dynamic(&node.route)
+                && let Some(handler) = node.handler(method)
+            {
+                return Some((handler, HashMap::new(), &node.route));
+            }
+        }
+
+        // Each bucket is pre-sorted, so its first hit is its most specific one;
+        // equally specific routes go in registration order.
+        self.buckets(path)
+            .filter_map(|routes| {
+                routes.iter().find_map(|(route, rank, seq)| {
+                    let node = self.router.get(route)?;
+                    let handler = node.handler(method)?;
+                    let params = self.match_path(route, path)?;
+                    Some(((Reverse(*rank), *seq), handler, params, route.as_str()))
+                })
+            })
+            .min_by_key(|(key, ..)| *key)
+            .map(|(_, handler, params, route)| (handler, params, route))
+    }
+
+    /// The dynamic route buckets that can match `path`. A dynamic route can only
+    /// match when its leading static segments are a prefix of the path.
+    fn buckets<'a>(&'a self, path: &str) -> impl Iterator<Item = &'a Vec<(String, Rank, usize)>> {
+        let inner = path.trim_matches('/');
+
+        std::iter::once("")
+            .chain(inner.match_indices('/').map(|(i, _)| &inner[..i]))
+            .chain(std::iter::once(inner).filter(|p| !p.is_empty()))
+            .filter_map(|key| self.dynamic.get(key))
+    }
+}
+
+/// The registered forms a static route for `path` may have, with and without a trailing slash.
+fn static_candidates(path: &str) -> [String; 3] {
+    let trimmed = path.trim_end_matches('/');
+    [
+        path.to_string(),
+        trimmed.to_string(),
+        format!("{}/", trimmed),
+    ]
+}
+
+fn scope_matches(scope: &str, path: &str) -> bool {
+    let mut path_segments = path.trim_start_matches('/').split('/');
+
+    for scope_seg in scope.trim_matches('/').split('/') {
+        if scope_seg.starts_with('*') || scope_seg.ends_with('*') {
+            return true;
+        }
+
+        match path_segments.next() {
+            Some(path_seg) if scope_seg.starts_with(':') => {
+                if path_seg.is_empty() {
+                    return false;
                 }
             }
+            Some(path_seg) if path_seg == scope_seg => {}
+            _ => return false,
+        }
+    }
+
+    true
+}
+
+pub(crate) fn slash_redirect(route: &str, path: &str) -> Option<String> {
+    if path == "/"
+        || route
+            .split('/')
+            .any(|s| s.starts_with('*') || s.ends_with('*'))
+    {
+        return None;
+    }
+
+    let path = normalize(path);
+    if path.starts_with("/\\") {
+        return None;
+    }
+
+    let wants_slash = route != "/" && route.ends_with('/');
+    let trimmed = path.trim_end_matches('/');
+
+    match (wants_slash, path.ends_with('/')) {
+        (true, false) => Some(format!("{}/", path)),
+        (false, true) if trimmed.is_empty() => Some("/".to_string()),
+        (false, true) => Some(trimmed.to_string()),
+        _ => None,
+    }
+}
+
+pub(crate) fn normalize(route: &str) -> String {
+    let mut out = String::with_capacity(route.len() + 1);
+
+    for c in route.chars() {
+        if c == '/' && out.ends_with('/') {
+            continue;
+        }
+        if out.is_empty() && c != '/' {
+            out.push('/');
+        }
+        out.push(c);
+    }
+
+    if out.is_empty() {
+        out.push('/');
+    }
+    out
+}
+
+fn shape(route: &str) -> String {
+    route
+        .trim_matches('/')
+        .split('/')
+        .map(|s| {
+            if s.starts_with(':') {
+                ":"
+            } else if s.starts_with('*') || s.ends_with('*') {
+                "*"
+            } else {
+                s
+            }
+        })
+        .collect::<Vec<_>>()
+        .join("/")
+}
+
+/// Leading static segments of a route, which every matching path starts with.
+fn bucket(route: &str) -> String {
+    route
+        .trim_matches('/')
+        .split('/')
+        .take_while(|s| !is_dynamic(s))
+        .collect::<Vec<_>>()
+        .join("/")
+}
+
+fn is_dynamic(route: &str) -> bool {
+    route
+        .split('/')
+        .any(|s| s.starts_with(':') || s.starts_with('*') || s.ends_with('*'))
+}
+
+fn rank(route: &str) -> Rank {
+    let mut rank = (0, 0, true);
+
+    for s in route.split('/').filter(|s| !s.is_empty()) {
+        if s.starts_with('*') || s.ends_with('*') {
+            rank.2 = false;
+        } else if s.starts_with(':') {
+            rank.1 += 1;
+        } else {
+            rank.0 += 1;
         }
+    }
 
-        best_match.map(|(handler, params, _)| (handler, params))
+    rank
+}
+
+fn strip_port(host: &str) -> &str {
+    if host.starts_with('[') {
+        return host.split_inclusive(']').next().unwrap_or(host);
     }
+
+    host.rsplit_once(':').map(|(h, _)| h).unwrap_or(host)
 }
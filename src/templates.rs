@@ -0,0 +1,49 @@
+use std::{error::Error as StdError, sync::RwLock};
+
+use serde::Serialize;
+use tera::{Context, Tera};
+
+use crate::types::BoltError;
+
+pub(crate) struct Templates {
+    tera: RwLock<Tera>,
+}
+
+impl Templates {
+    pub(crate) fn new(dir: &str) -> Result<Self, BoltError> {
+        let glob = format!("{}/**/*", dir.trim_end_matches('/'));
+
+        Ok(Self {
+            tera: RwLock::new(Tera::new(&glob)?),
+        })
+    }
+
+    pub(crate) fn render<T: Serialize>(&self, name: &str, context: &T) -> Result<String, String> {
+        let context = Context::from_serialize(context).map_err(|e| describe(&e))?;
+
+        #[cfg(debug_assertions)]
+        if let Ok(mut tera) = self.tera.write() {
+            tera.full_reload().map_err(|e| describe(&e))?;
+        }
+
+        let tera = self
+            .tera
+            .read()
+            .map_err(|_| "Template engine unavailable".to_string())?;
+
+        tera.render(name, &context).map_err(|e| describe(&e))
+    }
+}
+
+fn describe(error: &tera::Error) -> String {
+    let mut message = error.to_string();
+    let mut source = error.source();
+
+    while let Some(e) = source {
+        message.push_str(": ");
+        message.push_str(&e.to_string());
+        source = e.source();
+    }
+
+    message
+}
@@ -0,0 +1,117 @@
+use std::net::SocketAddr;
+
+use bytes::Bytes;
+use http_body_util::{BodyExt, Full};
+use hyper::{Method, Request};
+use hyper_util::{
+    client::legacy::{Client as HyperClient, connect::HttpConnector},
+    rt::TokioExecutor,
+};
+use serde::Serialize;
+use tokio::{net::TcpListener, sync::oneshot, task::JoinHandle};
+
+use crate::{App, client::ClientResponse, types::BoltError, types::Mode};
+
+pub struct TestClient {
+    addr: SocketAddr,
+    client: HyperClient<HttpConnector, Full<Bytes>>,
+    shutdown: Option<oneshot::Sender<()>>,
+    server: JoinHandle<()>,
+}
+
+impl TestClient {
+    pub async fn start(app: App) -> Result<Self, BoltError> {
+        let listener = TcpListener::bind("127.0.0.1:0").await?;
+        let addr = listener.local_addr()?;
+        let (tx, rx) = oneshot::channel::<()>();
+
+        let server = tokio::spawn(async move {
+            let shutdown = Box::pin(async move {
+                let _ = rx.await;
+            });
+
+            if let Err(e) = app
+                .run_listener_with_shutdown(listener, Mode::Http1, shutdown)
+                .await
+            {
+                tracing::error!("Test server error: {}", e);
+            }
+        });
+
+        Ok(Self {
+            addr,
+            client: HyperClient::builder(TokioExecutor::new()).build_http(),
+            shutdown: Some(tx),
+            server,
+        })
+    }
+
+    pub fn addr(&self) -> SocketAddr {
+        self.addr
+    }
+
+    pub fn url(&self, path: &str) -> String {
+        format!("http://{}{}", self.addr, path)
+    }
+
+    pub async fn request(
+        &self,
+        method: Method,
+        path: &str,
+        headers: &[(&str, &str)],
+        body: impl Into<Bytes>,
+    ) -> Result<ClientResponse, BoltError> {
+        let mut builder = Request::builder().method(method).uri(self.url(path));
+        for (name, value) in headers {
+            builder = builder.header(*name, *value);
+        }
+
+        let req = builder.body(Full::new(body.into()))?;
+        let resp = self.client.request(req).await?;
+        let (parts, body) = resp.into_parts();
+
+        Ok(ClientResponse {
+            status: parts.status.as_u16(),
+            headers: parts.headers,
+            body: body.collect().await?.to_bytes(),
+        })
+    }
+
+    pub async fn get(&self, path: &str) -> Result<ClientResponse, BoltError> {
+        self.request(Method::GET, path, &[], Bytes::new()).await
+    }
+
+    pub async fn post(
+        &self,
+        path: &str,
+        body: impl Into<Bytes>,
+    ) -> Result<ClientResponse, BoltError> {
+        self.request(Method::POST, path, &[], body).await
+    }
+
+    pub async fn json<T: Serialize + ?Sized>(
+        &self,
+        method: Method,
+        path: &str,
+        body: &T,
+    ) -> Result<ClientResponse, BoltError> {
+        let body = serde_json::to_vec(body)?;
+        self.request(method, path, &[("Content-Type", "application/json")], body)
+            .await
+    }
+
+    pub async fn shutdown(mut self) {
+        if let Some(tx) = self.shutdown.take() {
+            let _ = tx.send(());
+        }
+        let _ = (&mut self.server).await;
+    }
+}
+
+impl Drop for TestClient {
+    fn drop(&mut self) {
+        if let Some(tx) = self.shutdown.take() {
+            let _ = tx.send(());
+        }
+    }
+}
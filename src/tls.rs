@@ -3,29 +3,395 @@ use std::{
         self, Cursor,
         ErrorKind::{InvalidData, InvalidInput},
     },
-    sync::Arc,
+    sync::{Arc, RwLock},
 };
 
+use rustls::crypto::CryptoProvider;
 use rustls::pki_types::{CertificateDer, PrivateKeyDer};
-use rustls::{ServerConfig, version::TLS13};
-use rustls_pemfile::{certs, pkcs8_private_keys, rsa_private_keys};
+use rustls::server::{
+    ClientHello, ResolvesServerCert, WebPkiClientVerifier, danger::ClientCertVerifier,
+};
+use rustls::sign::CertifiedKey;
+use rustls::{
+    RootCertStore, ServerConfig, SupportedProtocolVersion,
+    version::{TLS12, TLS13},
+};
+use rustls_pemfile::{Item, certs, read_all};
+use simple_asn1::{ASN1Block, ASN1Class, BigUint, from_der};
+use tokio_rustls::TlsAcceptor;
 
-pub fn tls_config(cert_path: &str, key_path: &str) -> io::Result<Arc<ServerConfig>> {
-    let certs = load_certs(cert_path)?;
-    let key = load_private_key(key_path)?;
+use crate::types::{ClientCert, Mode};
+
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum TlsVersion {
+    Tls12,
+    Tls13,
+}
+
+#[derive(Debug, Clone, Default, PartialEq, Eq)]
+pub enum ClientAuth {
+    #[default]
+    None,
+    Optional(String),
+    Required(String),
+}
+
+#[derive(Debug, Clone)]
+pub struct TlsOptions {
+    pub versions: Vec<TlsVersion>,
+    pub cipher_suites: Option<Vec<String>>,
+    pub client_auth: ClientAuth,
+}
+
+impl Default for TlsOptions {
+    fn default() -> Self {
+        Self {
+            versions: vec![TlsVersion::Tls12, TlsVersion::Tls13],
+            cipher_suites: None,
+            client_auth: ClientAuth::None,
+        }
+    }
+}
+
+#[derive(Debug, Default)]
+struct CertResolver {
+    current: RwLock<Option<Arc<CertifiedKey>>>,
+}
+
+impl ResolvesServerCert for CertResolver {
+    fn resolve(&self, _client_hello: ClientHello<'_>) -> Option<Arc<CertifiedKey>> {
+        read(&self.current)
+    }
+}
+
+#[derive(Clone, Default)]
+pub struct TlsHandle {
+    config: Arc<RwLock<Option<Arc<ServerConfig>>>>,
+    options: Arc<RwLock<TlsOptions>>,
+    alpn: Arc<RwLock<Vec<Vec<u8>>>>,
+    resolver: Arc<CertResolver>,
+}
+
+impl TlsHandle {
+    pub fn reload(&self, cert_path: &str, key_path: &str) -> io::Result<()> {
+        let options = read(&self.options);
+        let provider = provider(&options)?;
+        let key = certified_key(cert_path, key_path, &provider)?;
+
+        let config = match read(&self.config) {
+            Some(_) => None,
+            None => Some(server_config(
+                &options,
+                provider,
+                read(&self.alpn),
+                self.resolver.clone(),
+            )?),
+        };
+
+        write(&self.resolver.current, Some(Arc::new(key)));
+        if let Some(config) = config {
+            write(&self.config, Some(config));
+        }
+
+        Ok(())
+    }
 
-    let mut config = ServerConfig::builder_with_protocol_versions(&[&TLS13])
-        .with_no_client_auth()
-        .with_single_cert(certs, key)
+    pub fn set_options(&self, options: TlsOptions) -> io::Result<()> {
+        if read(&self.config).is_some() {
+            let config = server_config(
+                &options,
+                provider(&options)?,
+                read(&self.alpn),
+                self.resolver.clone(),
+            )?;
+            write(&self.config, Some(config));
+        }
+
+        write(&self.options, options);
+        Ok(())
+    }
+
+    pub(crate) fn set_alpn(&self, mode: Mode) -> io::Result<()> {
+        let protocols = match mode {
+            Mode::Http1 => vec![b"http/1.1".to_vec()],
+            Mode::Http2 => vec![b"h2".to_vec()],
+            Mode::Auto => vec![b"h2".to_vec(), b"http/1.1".to_vec()],
+        };
+
+        if let Some(config) = read(&self.config) {
+            let mut config = (*config).clone();
+            config.alpn_protocols = protocols.clone();
+            write(&self.config, Some(Arc::new(config)));
+        }
+
+        write(&self.alpn, protocols);
+        Ok(())
+    }
+
+    pub fn is_enabled(&self) -> bool {
+        self.acceptor().is_some()
+    }
+
+    pub(crate) fn acceptor(&self) -> Option<TlsAcceptor> {
+        read(&self.config).map(TlsAcceptor::from)
+    }
+}
+
+fn read<T: Clone>(lock: &RwLock<T>) -> T {
+    match lock.read() {
+        Ok(value) => value.clone(),
+        Err(poisoned) => poisoned.into_inner().clone(),
+    }
+}
+
+fn write<T>(lock: &RwLock<T>, value: T) {
+    match lock.write() {
+        Ok(mut current) => *current = value,
+        Err(poisoned) => *poisoned.into_inner() = value,
+    }
+}
+
+fn server_config(
+    options: &TlsOptions,
+    provider: CryptoProvider,
+    alpn: Vec<Vec<u8>>,
+    resolver: Arc<CertResolver>,
+) -> io::Result<Arc<ServerConfig>> {
+    let versions: Vec<&'static SupportedProtocolVersion> = options
+        .versions
+        .iter()
+        .map(|v| match v {
+            TlsVersion::Tls12 => &TLS12,
+            TlsVersion::Tls13 => &TLS13,
+        })
+        .collect();
+
+    if versions.is_empty() {
+        return Err(io::Error::new(InvalidInput, "No TLS versions enabled"));
+    }
+
+    let provider = Arc::new(provider);
+    let builder = ServerConfig::builder_with_provider(provider.clone())
+        .with_protocol_versions(&versions)
         .map_err(|e| io::Error::new(InvalidInput, e.to_string()))?;
 
-    config.alpn_protocols = vec![b"h2".to_vec(), b"http/1.1".to_vec()];
+    let builder = match &options.client_auth {
+        ClientAuth::None => builder.with_no_client_auth(),
+        ClientAuth::Optional(ca_path) => {
+            builder.with_client_cert_verifier(client_verifier(ca_path, provider, true)?)
+        }
+        ClientAuth::Required(ca_path) => {
+            builder.with_client_cert_verifier(client_verifier(ca_path, provider, false)?)
+        }
+    };
+
+    let mut config = builder.with_cert_resolver(resolver);
+
+    config.alpn_protocols = alpn;
 
     Ok(Arc::new(config))
 }
 
+fn client_verifier(
+    ca_path: &str,
+    provider: Arc<CryptoProvider>,
+    optional: bool,
+) -> io::Result<Arc<dyn ClientCertVerifier>> {
+    let mut roots = RootCertStore::empty();
+    for cert in load_certs(ca_path)? {
+        roots
+            .add(cert)
+            .map_err(|e| io::Error::new(InvalidData, format!("{}: {}", ca_path, e)))?;
+    }
+
+    let mut builder = WebPkiClientVerifier::builder_with_provider(Arc::new(roots), provider);
+    if optional {
+        builder = builder.allow_unauthenticated();
+    }
+
+    builder
+        .build()
+        .map_err(|e| io::Error::new(InvalidInput, format!("{}: {}", ca_path, e)))
+}
+
+pub(crate) fn client_cert(der: &[u8]) -> ClientCert {
+    let mut cert = ClientCert {
+        der: der.to_vec(),
+        ..Default::default()
+    };
+
+    let Ok(blocks) = from_der(der) else {
+        return cert;
+    };
+    let Some(ASN1Block::Sequence(_, parts)) = blocks.first() else {
+        return cert;
+    };
+    let Some(ASN1Block::Sequence(_, tbs)) = parts.first() else {
+        return cert;
+    };
+
+    let offset = usize::from(matches!(tbs.first(), Some(ASN1Block::Explicit(..))));
+    if let Some(ASN1Block::Sequence(_, rdns)) = tbs.get(offset + 4) {
+        cert.subject = distinguished_name(rdns);
+    }
+
+    for block in tbs.iter().skip(offset + 6) {
+        if let ASN1Block::Explicit(_, _, tag, inner) = block
+            && *tag == BigUint::from(3u8)
+            && let ASN1Block::Sequence(_, extensions) = inner.as_ref()
+        {
+            for extension in extensions {
+                subject_alt_names(extension, &mut cert);
+            }
+        }
+    }
+
+    cert
+}
+
+fn oid(block: &ASN1Block) -> Vec<u64> {
+    match block {
+        ASN1Block::ObjectIdentifier(_, oid) => oid.as_vec().unwrap_or_default(),
+        _ => Vec::new(),
+    }
+}
+
+fn distinguished_name(rdns: &[ASN1Block]) -> String {
+    let mut parts = Vec::new();
+
+    for rdn in rdns.iter().rev() {
+        let ASN1Block::Set(_, attributes) = rdn else {
+            continue;
+        };
+
+        for attribute in attributes {
+            let ASN1Block::Sequence(_, pair) = attribute else {
+                continue;
+            };
+            let (Some(key), Some(value)) = (pair.first(), pair.get(1)) else {
+                continue;
+            };
+
+            let value = match value {
+                ASN1Block::UTF8String(_, v)
+                | ASN1Block::PrintableString(_, v)
+                | ASN1Block::IA5String(_, v)
+                | ASN1Block::TeletexString(_, v)
+                | ASN1Block::BMPString(_, v)
+                | ASN1Block::UniversalString(_, v) => v.clone(),
+                _ => continue,
+            };
+
+            let oid = oid(key);
+            let name = match oid.as_slice() {
+                [2, 5, 4, 3] => "CN".to_string(),
+                [2, 5, 4, 6] => "C".to_string(),
+                [2, 5, 4, 7] => "L".to_string(),
+                [2, 5, 4, 8] => "ST".to_string(),
+                [2, 5, 4, 10] => "O".to_string(),
+                [2, 5, 4, 11] => "OU".to_string(),
+                [1, 2, 840, 113549, 1, 9, 1] => "emailAddress".to_string(),
+                _ => oid
+                    .iter()
+                    .map(|n| n.to_string())
+                    .collect::<Vec<_>>()
+                    .join("."),
+            };
+
+            parts.push(format!("{}={}", name, value));
+        }
+    }
+
+    parts.join(",")
+}
+
+fn subject_alt_names(extension: &ASN1Block, cert: &mut ClientCert) {
+    let ASN1Block::Sequence(_, fields) = extension else {
+        return;
+    };
+    if fields.first().map(oid).as_deref() != Some(&[2, 5, 29, 17]) {
+        return;
+    }
+    let Some(ASN1Block::OctetString(_, value)) = fields.last() else {
+        return;
+    };
+    let Ok(names) = from_der(value) else {
+        return;
+    };
+    let Some(ASN1Block::Sequence(_, names)) = names.first() else {
+        return;
+    };
+
+    for name in names {
+        let ASN1Block::Unknown(ASN1Class::ContextSpecific, false, _, tag, bytes) = name else {
+            continue;
+        };
+        let value = String::from_utf8_lossy(bytes).into_owned();
+
+        if *tag == BigUint::from(1u8) {
+            cert.emails.push(value);
+        } else if *tag == BigUint::from(2u8) {
+            cert.dns_names.push(value);
+        } else if *tag == BigUint::from(6u8) {
+            cert.uris.push(value);
+        }
+    }
+}
+
+fn certified_key(
+    cert_path: &str,
+    key_path: &str,
+    provider: &CryptoProvider,
+) -> io::Result<CertifiedKey> {
+    let certs = load_certs(cert_path)?;
+    let key = load_private_key(key_path)?;
+
+    CertifiedKey::from_der(certs, key, provider).map_err(|e| {
+        io::Error::new(
+            InvalidInput,
+            format!(
+                "Unusable certificate/key pair ({}, {}): {}",
+                cert_path, key_path, e
+            ),
+        )
+    })
+}
+
+fn provider(options: &TlsOptions) -> io::Result<CryptoProvider> {
+    let mut provider = CryptoProvider::get_default()
+        .map(|p| p.as_ref().clone())
+        .unwrap_or_else(rustls::crypto::aws_lc_rs::default_provider);
+
+    if let Some(names) = &options.cipher_suites {
+        let mut suites = Vec::with_capacity(names.len());
+
+        for name in names {
+            let suite = provider
+                .cipher_suites
+                .iter()
+                .find(|s| {
+                    s.suite()
+                        .as_str()
+                        .is_some_and(|n| n.eq_ignore_ascii_case(name))
+                })
+                .ok_or_else(|| {
+                    io::Error::new(InvalidInput, format!("Unknown cipher suite: {}", name))
+                })?;
+            suites.push(*suite);
+        }
+
+        provider.cipher_suites = suites;
+    }
+
+    Ok(provider)
+}
+
+fn read_pem(path: &str) -> io::Result<Vec<u8>> {
+    std::fs::read(path).map_err(|e| io::Error::new(e.kind(), format!("{}: {}", path, e)))
+}
+
 fn load_certs(path: &str) -> io::Result<Vec<CertificateDer<'static>>> {
-    let input = std::fs::read(path)?;
+    let input = read_pem(path)?;
     let mut cursor = Cursor::new(input);
 
     let mut out = Vec::new();
@@ -33,23 +399,38 @@ fn load_certs(path: &str) -> io::Result<Vec<CertificateDer<'static>>> {
         out.push(CertificateDer::from(cert?));
     }
 
+    if out.is_empty() {
+        return Err(io::Error::new(
+            InvalidData,
+            format!("{}: no certificates found", path),
+        ));
+    }
+
     Ok(out)
 }
 
 fn load_private_key(path: &str) -> io::Result<PrivateKeyDer<'static>> {
-    let input = std::fs::read(path)?;
-
+    let input = read_pem(path)?;
     let mut cursor = Cursor::new(&input);
-    for key in pkcs8_private_keys(&mut cursor) {
-        let pk = key?;
-        return Ok(PrivateKeyDer::from(pk));
-    }
+    let mut last_error = None;
 
-    let mut cursor = Cursor::new(&input);
-    for key in rsa_private_keys(&mut cursor) {
-        let rk = key?;
-        return Ok(PrivateKeyDer::from(rk));
+    for item in read_all(&mut cursor) {
+        match item {
+            Ok(Item::Pkcs8Key(key)) => return Ok(PrivateKeyDer::from(key)),
+            Ok(Item::Pkcs1Key(key)) => return Ok(PrivateKeyDer::from(key)),
+            Ok(Item::Sec1Key(key)) => return Ok(PrivateKeyDer::from(key)),
+            Ok(_) => {}
+            Err(e) => last_error = Some(e),
+        }
     }
 
-    Err(io::Error::new(InvalidData, "no valid private key found"))
+    let reason = match last_error {
+        Some(e) => format!("{}: no valid private key found ({})", path, e),
+        None => format!(
+            "{}: no PKCS#8, PKCS#1 (RSA) or SEC1 (EC) private key found",
+            path
+        ),
+    };
+
+    Err(io::Error::new(InvalidData, reason))
 }
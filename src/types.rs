@@ -1,7 +1,15 @@
-use crate::{request::RequestBody, response::ResponseWriter};
+use crate::{
+    client::ClientError,
+    http::{HttpError, ProblemDetails, StatusCode},
+    request::RequestBody,
+    response::ResponseWriter,
+};
 use async_trait::async_trait;
 use std::collections::HashMap;
 use std::error::Error as StdError;
+use std::path::PathBuf;
+use std::pin::Pin;
+use std::time::Duration;
 
 #[derive(Hash, Eq, PartialEq, Debug, Clone, Copy)]
 pub enum Method {
@@ -13,13 +21,49 @@ pub enum Method {
     OPTIONS,
     HEAD,
     TRACE,
+    CONNECT,
 }
 
-#[derive(Eq, PartialEq)]
+impl Method {
+    pub const ALL: [Method; 9] = [
+        Method::GET,
+        Method::POST,
+        Method::PUT,
+        Method::PATCH,
+        Method::DELETE,
+        Method::OPTIONS,
+        Method::HEAD,
+        Method::TRACE,
+        Method::CONNECT,
+    ];
+
+    pub fn as_str(&self) -> &'static str {
+        match self {
+            Method::GET => "GET",
+            Method::POST => "POST",
+            Method::PUT => "PUT",
+            Method::PATCH => "PATCH",
+            Method::DELETE => "DELETE",
+            Method::OPTIONS => "OPTIONS",
+            Method::HEAD => "HEAD",
+            Method::TRACE => "TRACE",
+            Method::CONNECT => "CONNECT",
+        }
+    }
+}
+
+impl std::fmt::Display for Method {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        f.write_str(self.as_str())
+    }
+}
+
+#[derive(Clone, Copy, Eq, PartialEq)]
 #[allow(dead_code)]
 pub enum Mode {
     Http1,
     Http2,
+    Auto,
 }
 
 #[allow(dead_code)]
@@ -37,11 +81,144 @@ pub struct FormData {
     pub fields: HashMap<String, String>,
 }
 
+#[derive(Debug, Clone)]
+pub struct FormDataConfig {
+    pub max_file_size: Option<u64>,
+    pub max_files: Option<usize>,
+    pub max_parts: Option<usize>,
+    pub temp_dir: Option<PathBuf>,
+}
+
+impl Default for FormDataConfig {
+    fn default() -> Self {
+        Self {
+            max_file_size: Some(10 * 1024 * 1024),
+            max_files: Some(10),
+            max_parts: Some(100),
+            temp_dir: None,
+        }
+    }
+}
+
+impl FormDataConfig {
+    pub fn temp_dir(&self) -> PathBuf {
+        self.temp_dir.clone().unwrap_or_else(std::env::temp_dir)
+    }
+}
+
+#[derive(Debug, Clone, Default)]
+pub struct TlsInfo {
+    pub sni: Option<String>,
+    pub alpn: Option<String>,
+    pub version: Option<String>,
+    pub client_cert: Option<ClientCert>,
+}
+
+#[derive(Debug, Clone, Default)]
+pub struct ClientCert {
+    pub subject: String,
+    pub dns_names: Vec<String>,
+    pub emails: Vec<String>,
+    pub uris: Vec<String>,
+    pub der: Vec<u8>,
+}
+
+#[derive(Debug, Clone, Copy)]
+pub struct Timeouts {
+    pub header_read: Duration,
+    pub body_read: Duration,
+    pub handler: Duration,
+    pub idle_keepalive: Duration,
+    pub total: Option<Duration>,
+}
+
+impl Default for Timeouts {
+    fn default() -> Self {
+        Self {
+            header_read: Duration::from_secs(10),
+            body_read: Duration::from_secs(30),
+            handler: Duration::from_secs(30),
+            idle_keepalive: Duration::from_secs(75),
+            total: None,
+        }
+    }
+}
+
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum TimeoutKind {
+    Body,
+    Upstream,
+    Handler,
+}
+
+#[derive(Debug, Clone, Copy, Default, PartialEq, Eq)]
+pub enum OverloadPolicy {
+    Drop,
+    #[default]
+    Reject,
+    Wait(Duration),
+}
+
+#[derive(Debug, Clone, Copy)]
+pub struct Http2Settings {
+    pub max_concurrent_streams: u32,
+    pub initial_stream_window_size: u32,
+    pub initial_connection_window_size: u32,
+    pub max_frame_size: u32,
+    pub max_header_list_size: u32,
+    pub max_pending_accept_reset_streams: usize,
+    pub keep_alive_interval: Option<Duration>,
+    pub keep_alive_timeout: Duration,
+}
+
+impl Default for Http2Settings {
+    fn default() -> Self {
+        Self {
+            max_concurrent_streams: 100,
+            initial_stream_window_size: 1024 * 1024,
+            initial_connection_window_size: 1024 * 1024,
+            max_frame_size: 16 * 1024,
+            max_header_list_size: 32 * 1024,
+            max_pending_accept_reset_streams: 20,
+            keep_alive_interval: Some(Duration::from_secs(20)),
+            keep_alive_timeout: Duration::from_secs(20),
+        }
+    }
+}
+
+pub enum MiddlewareOutcome {
+    Continue,
+    Stop,
+    Error(HttpError),
+}
+
 #[async_trait]
 pub trait Middleware: Send + Sync {
+    async fn handle(&self, req: &mut RequestBody, res: &mut ResponseWriter) -> MiddlewareOutcome;
+
+    async fn after(&self, _req: &mut RequestBody, _res: &mut ResponseWriter) {}
+}
+
+/// The pre-`MiddlewareOutcome` middleware form. Register it through `LegacyMiddleware`,
+/// which runs it and continues.
+#[deprecated(note = "implement `Middleware::handle` and return a `MiddlewareOutcome` instead")]
+#[async_trait]
+pub trait RunMiddleware: Send + Sync {
     async fn run(&self, req: &mut RequestBody, res: &mut ResponseWriter);
 }
 
+/// Adapts a `RunMiddleware` to `Middleware`.
+pub struct LegacyMiddleware<M>(pub M);
+
+#[allow(deprecated)]
+#[async_trait]
+impl<M: RunMiddleware> Middleware for LegacyMiddleware<M> {
+    async fn handle(&self, req: &mut RequestBody, res: &mut ResponseWriter) -> MiddlewareOutcome {
+        self.0.run(req, res).await;
+        MiddlewareOutcome::Continue
+    }
+}
+
 #[async_trait]
 pub trait ErrorHandler: Send + Sync {
     async fn run(&self, msg: String, res: &mut ResponseWriter);
@@ -52,6 +229,76 @@ pub trait Handler: Send + Sync {
     async fn run(&self, req: &mut RequestBody, res: &mut ResponseWriter);
 }
 
+pub trait HandlerResult {
+    fn apply(self, res: &mut ResponseWriter);
+}
+
+impl HandlerResult for () {
+    fn apply(self, _res: &mut ResponseWriter) {}
+}
+
+impl<E: Into<BoltError>> HandlerResult for Result<(), E> {
+    fn apply(self, res: &mut ResponseWriter) {
+        if let Err(e) = self {
+            let e: BoltError = e.into();
+            if let Some(problem) = e.downcast_ref::<ProblemDetails>() {
+                res.problem(problem);
+                return;
+            }
+
+            if let Some(ClientError::Timeout | ClientError::ConnectTimeout) =
+                e.downcast_ref::<ClientError>()
+            {
+                res.timed_out(TimeoutKind::Upstream, &e.to_string());
+                return;
+            }
+
+            match e.downcast_ref::<HttpError>() {
+                Some(err) if err.status == StatusCode::RequestTimeout => {
+                    res.timed_out(TimeoutKind::Body, &err.message)
+                }
+                Some(err) => res.error(err.status, &err.message),
+                None => res.error(StatusCode::InternalServerError, &e.to_string()),
+            };
+        }
+    }
+}
+
+pub type HandlerFuture<'a> = Pin<Box<dyn Future<Output = ()> + Send + 'a>>;
+
+pub trait HandlerFn<'a>: Send + Sync {
+    type Output: HandlerResult;
+    type Future: Future<Output = Self::Output> + Send + 'a;
+
+    fn call(&self, req: &'a mut RequestBody, res: &'a mut ResponseWriter) -> Self::Future;
+}
+
+impl<'a, F, Fut> HandlerFn<'a> for F
+where
+    F: Fn(&'a mut RequestBody, &'a mut ResponseWriter) -> Fut + Send + Sync,
+    Fut: Future + Send + 'a,
+    Fut::Output: HandlerResult,
+{
+    type Output = Fut::Output;
+    type Future = Fut;
+
+    fn call(&self, req: &'a mut RequestBody, res: &'a mut ResponseWriter) -> Fut {
+        self(req, res)
+    }
+}
+
+#[async_trait]
+impl<F, O> Handler for F
+where
+    F: for<'a> HandlerFn<'a, Output = O>,
+    O: HandlerResult + Send,
+{
+    async fn run(&self, req: &mut RequestBody, res: &mut ResponseWriter) {
+        let result = self.call(req, res).await;
+        result.apply(res);
+    }
+}
+
 pub type BoltError = Box<dyn StdError + Send + Sync>;
 
 #[allow(dead_code)]
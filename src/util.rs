@@ -0,0 +1,35 @@
+use std::time::Duration;
+
+use crate::types::BoltError;
+
+/// Longest wait between two attempts, however long the backoff has grown.
+const MAX_BACKOFF: Duration = Duration::from_secs(30);
+
+pub async fn retry<F, Fut, T, E>(attempts: usize, backoff: Duration, mut op: F) -> Result<T, E>
+where
+    F: FnMut() -> Fut,
+    Fut: Future<Output = Result<T, E>>,
+{
+    let mut delay = backoff.min(MAX_BACKOFF);
+    let mut attempt = 1;
+
+    loop {
+        match op().await {
+            Ok(value) => return Ok(value),
+            Err(e) if attempt >= attempts => return Err(e),
+            Err(_) => {
+                tokio::time::sleep(delay).await;
+                delay = delay.saturating_mul(2).min(MAX_BACKOFF);
+                attempt += 1;
+            }
+        }
+    }
+}
+
+pub async fn blocking<F, T>(f: F) -> Result<T, BoltError>
+where
+    F: FnOnce() -> T + Send + 'static,
+    T: Send + 'static,
+{
+    Ok(tokio::task::spawn_blocking(f).await?)
+}
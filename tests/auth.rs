@@ -0,0 +1,90 @@
+#![cfg(all(feature = "testing", feature = "jwt"))]
+
+use bolt_web::{
+    App,
+    middleware::{Auth, Claims},
+    request::RequestBody,
+    response::ResponseWriter,
+    testing::TestClient,
+};
+use jsonwebtoken::{EncodingKey, Header};
+use serde_json::{Value, json};
+
+const SECRET: &[u8] = b"test-secret";
+
+async fn me(req: &mut RequestBody, res: &mut ResponseWriter) {
+    let sub: String = req.ext::<Claims>().unwrap().get("sub").unwrap();
+    res.text(&sub);
+}
+
+fn token(claims: Value) -> String {
+    jsonwebtoken::encode(
+        &Header::default(),
+        &claims,
+        &EncodingKey::from_secret(SECRET),
+    )
+    .unwrap()
+}
+
+fn exp() -> u64 {
+    jsonwebtoken::get_current_timestamp() + 300
+}
+
+fn app(auth: Auth) -> App {
+    let mut app = App::new();
+    app.set_banner(false);
+    app.middleware("/", None, auth);
+    app.get("/me", me);
+    app
+}
+
+async fn get_with(client: &TestClient, authorization: &str) -> (u16, String) {
+    let res = client
+        .request(
+            hyper::Method::GET,
+            "/me",
+            &[("authorization", authorization)],
+            "",
+        )
+        .await
+        .unwrap();
+
+    (res.status, String::from_utf8_lossy(&res.body).into_owned())
+}
+
+#[tokio::test]
+async fn bearer_scheme_is_case_insensitive() {
+    let client = TestClient::start(app(Auth::hs256(SECRET))).await.unwrap();
+    let token = token(json!({ "sub": "alice", "exp": exp() }));
+
+    for scheme in ["Bearer", "bearer", "BEARER"] {
+        let (status, body) = get_with(&client, &format!("{} {}", scheme, token)).await;
+        assert_eq!(status, 200, "{}", scheme);
+        assert_eq!(body, "alice");
+    }
+
+    let (status, _) = get_with(&client, &format!("Basic {}", token)).await;
+    assert_eq!(status, 401);
+
+    client.shutdown().await;
+}
+
+#[tokio::test]
+async fn configured_audience_and_issuer_are_required() {
+    let auth = Auth::hs256(SECRET).audience(&["api"]).issuer(&["bolt"]);
+    let client = TestClient::start(app(auth)).await.unwrap();
+
+    let valid = token(json!({ "sub": "alice", "exp": exp(), "aud": "api", "iss": "bolt" }));
+    let (status, _) = get_with(&client, &format!("Bearer {}", valid)).await;
+    assert_eq!(status, 200);
+
+    let no_aud = token(json!({ "sub": "alice", "exp": exp(), "iss": "bolt" }));
+    let (status, _) = get_with(&client, &format!("Bearer {}", no_aud)).await;
+    assert_eq!(status, 401);
+
+    let no_iss = token(json!({ "sub": "alice", "exp": exp(), "aud": "api" }));
+    let (status, _) = get_with(&client, &format!("Bearer {}", no_iss)).await;
+    assert_eq!(status, 401);
+
+    client.shutdown().await;
+}
@@ -0,0 +1,224 @@
+#![cfg(feature = "testing")]
+
+use std::{
+    sync::atomic::{AtomicUsize, Ordering},
+    time::Duration,
+};
+
+use bolt_web::{
+    App,
+    middleware::{Cache, Cors, RequestId},
+    request::RequestBody,
+    response::ResponseWriter,
+    testing::TestClient,
+    types::Method,
+};
+
+static FEED_CALLS: AtomicUsize = AtomicUsize::new(0);
+static COOKIE_CALLS: AtomicUsize = AtomicUsize::new(0);
+static PRIVATE_CALLS: AtomicUsize = AtomicUsize::new(0);
+static LOCALIZED_CALLS: AtomicUsize = AtomicUsize::new(0);
+
+async fn feed(_: &mut RequestBody, res: &mut ResponseWriter) {
+    let n = FEED_CALLS.fetch_add(1, Ordering::SeqCst);
+    res.text(&format!("feed {}", n));
+}
+
+async fn with_cookie(_: &mut RequestBody, res: &mut ResponseWriter) {
+    let n = COOKIE_CALLS.fetch_add(1, Ordering::SeqCst);
+    res.set_header("Set-Cookie", "sid=abc");
+    res.text(&format!("cookie {}", n));
+}
+
+async fn private(_: &mut RequestBody, res: &mut ResponseWriter) {
+    let n = PRIVATE_CALLS.fetch_add(1, Ordering::SeqCst);
+    res.set_header("Cache-Control", "private, max-age=60");
+    res.text(&format!("private {}", n));
+}
+
+async fn encoded(req: &mut RequestBody, res: &mut ResponseWriter) {
+    let gzip = req
+        .headers()
+        .get("accept-encoding")
+        .is_some_and(|v| v.to_str().unwrap_or_default().contains("gzip"));
+
+    if gzip {
+        res.set_header("Content-Encoding", "gzip");
+        res.text("gzipped");
+    } else {
+        res.text("plain");
+    }
+}
+
+async fn localized(_: &mut RequestBody, res: &mut ResponseWriter) {
+    let n = LOCALIZED_CALLS.fetch_add(1, Ordering::SeqCst);
+    res.set_header("Vary", "Accept-Language");
+    res.text(&format!("localized {}", n));
+}
+
+async fn hello(_: &mut RequestBody, res: &mut ResponseWriter) {
+    res.text("hello");
+}
+
+fn cached_app(ttl: Duration) -> App {
+    let mut app = App::new();
+    app.set_banner(false);
+    app.middleware("/", Some(Method::GET), Cache::new(ttl));
+    app.get("/feed", feed);
+    app.get("/cookie", with_cookie);
+    app.get("/private", private);
+    app.get("/encoded", encoded);
+    app.get("/localized", localized);
+    app
+}
+
+#[tokio::test]
+async fn hit_within_ttl_skips_handler_and_miss_after_expiry() {
+    let client = TestClient::start(cached_app(Duration::from_millis(200)))
+        .await
+        .unwrap();
+
+    let first = client.get("/feed").await.unwrap();
+    assert_eq!(first.body, "feed 0");
+    assert!(first.headers.get("x-cache").is_none());
+
+    let second = client.get("/feed").await.unwrap();
+    assert_eq!(second.body, "feed 0");
+    assert_eq!(second.headers["x-cache"], "HIT");
+    assert_eq!(FEED_CALLS.load(Ordering::SeqCst), 1);
+
+    tokio::time::sleep(Duration::from_millis(300)).await;
+
+    let third = client.get("/feed").await.unwrap();
+    assert_eq!(third.body, "feed 1");
+    assert!(third.headers.get("x-cache").is_none());
+    assert_eq!(FEED_CALLS.load(Ordering::SeqCst), 2);
+
+    client.shutdown().await;
+}
+
+#[tokio::test]
+async fn responses_setting_cookies_are_not_stored() {
+    let client = TestClient::start(cached_app(Duration::from_secs(5)))
+        .await
+        .unwrap();
+
+    client.get("/cookie").await.unwrap();
+    let res = client.get("/cookie").await.unwrap();
+
+    assert_eq!(res.body, "cookie 1");
+    assert!(res.headers.get("x-cache").is_none());
+
+    client.shutdown().await;
+}
+
+#[tokio::test]
+async fn private_responses_are_not_stored() {
+    let client = TestClient::start(cached_app(Duration::from_secs(5)))
+        .await
+        .unwrap();
+
+    client.get("/private").await.unwrap();
+    let res = client.get("/private").await.unwrap();
+
+    assert_eq!(res.body, "private 1");
+    assert!(res.headers.get("x-cache").is_none());
+
+    client.shutdown().await;
+}
+
+#[tokio::test]
+async fn encoded_bodies_are_only_replayed_to_matching_clients() {
+    let client = TestClient::start(cached_app(Duration::from_secs(5)))
+        .await
+        .unwrap();
+
+    let gzip = [("accept-encoding", "gzip")];
+    client
+        .request(hyper::Method::GET, "/encoded", &gzip, "")
+        .await
+        .unwrap();
+
+    let res = client.get("/encoded").await.unwrap();
+    assert_eq!(res.body, "plain");
+    assert!(res.headers.get("content-encoding").is_none());
+
+    let res = client
+        .request(hyper::Method::GET, "/encoded", &gzip, "")
+        .await
+        .unwrap();
+    assert_eq!(res.body, "gzipped");
+    assert_eq!(res.headers["x-cache"], "HIT");
+
+    client.shutdown().await;
+}
+
+#[tokio::test]
+async fn hits_keep_headers_set_earlier_for_this_request() {
+    let mut app = App::new();
+    app.set_banner(false);
+    app.middleware("/", None, RequestId::new().trust_incoming(false));
+    app.middleware(
+        "/",
+        None,
+        Cors::new()
+            .allow_origin("https://a.example")
+            .allow_origin("https://b.example"),
+    );
+    app.middleware("/", Some(Method::GET), Cache::new(Duration::from_secs(5)));
+    app.get("/hello", hello);
+
+    let client = TestClient::start(app).await.unwrap();
+
+    let first = client
+        .request(
+            hyper::Method::GET,
+            "/hello",
+            &[("origin", "https://a.example")],
+            "",
+        )
+        .await
+        .unwrap();
+    assert_eq!(
+        first.headers["access-control-allow-origin"],
+        "https://a.example"
+    );
+
+    let second = client
+        .request(
+            hyper::Method::GET,
+            "/hello",
+            &[("origin", "https://b.example")],
+            "",
+        )
+        .await
+        .unwrap();
+    assert_eq!(second.headers["x-cache"], "HIT");
+    assert_eq!(second.body, "hello");
+    assert_eq!(
+        second.headers["access-control-allow-origin"],
+        "https://b.example"
+    );
+    assert_eq!(second.headers.get_all("x-request-id").iter().count(), 1);
+    assert_ne!(
+        second.headers["x-request-id"],
+        first.headers["x-request-id"]
+    );
+
+    client.shutdown().await;
+}
+
+#[tokio::test]
+async fn responses_varying_on_unkeyed_headers_are_not_stored() {
+    let client = TestClient::start(cached_app(Duration::from_secs(5)))
+        .await
+        .unwrap();
+
+    client.get("/localized").await.unwrap();
+    let res = client.get("/localized").await.unwrap();
+
+    assert_eq!(res.body, "localized 1");
+    assert!(res.headers.get("x-cache").is_none());
+
+    client.shutdown().await;
+}
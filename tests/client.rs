@@ -0,0 +1,192 @@
+#![cfg(feature = "testing")]
+
+use bolt_web::{
+    App,
+    client::{Client, ClientResponse},
+    http::StatusCode,
+    request::RequestBody,
+    response::ResponseWriter,
+    testing::TestClient,
+    types::BoltError,
+};
+use bytes::Bytes;
+use futures_util::{StreamExt, stream};
+
+const CHUNK: usize = 64 * 1024;
+const CHUNKS: usize = 128;
+
+async fn large(_: &mut RequestBody, res: &mut ResponseWriter) {
+    res.set_header("X-Stream", "yes");
+    let tx = res.stream_channel();
+
+    tokio::spawn(async move {
+        for i in 0..CHUNKS {
+            let chunk = Bytes::from(vec![i as u8; CHUNK]);
+            if tx.send(chunk).await.is_err() {
+                break;
+            }
+        }
+    });
+}
+
+async fn chunks(_: &mut RequestBody, res: &mut ResponseWriter) {
+    let tx = res.stream_channel();
+
+    tokio::spawn(async move {
+        for chunk in ["one\n", "two\n", "three\n"] {
+            tokio::time::sleep(std::time::Duration::from_millis(10)).await;
+            if tx.send(Bytes::from(chunk)).await.is_err() {
+                break;
+            }
+        }
+    });
+}
+
+async fn count(req: &mut RequestBody, res: &mut ResponseWriter) -> Result<(), BoltError> {
+    let body = req.bytes().await?;
+    res.status(StatusCode::Created)
+        .text(&body.len().to_string());
+    Ok(())
+}
+
+fn app() -> App {
+    let mut app = App::new();
+    app.set_banner(false);
+    app.get("/large", large);
+    app.get("/chunks", chunks);
+    app.post("/count", count);
+    app
+}
+
+#[tokio::test]
+async fn get_stream_yields_a_large_body_chunk_by_chunk() {
+    let server = TestClient::start(app()).await.unwrap();
+
+    let res = Client::new()
+        .get_stream(&server.url("/large"), &None)
+        .await
+        .unwrap();
+    assert_eq!(res.status, 200);
+    assert_eq!(res.headers["x-stream"], "yes");
+
+    let mut body = res.body;
+    let (mut total, mut frames, mut largest) = (0, 0, 0);
+    while let Some(chunk) = body.next().await {
+        let chunk = chunk.unwrap();
+        total += chunk.len();
+        largest = largest.max(chunk.len());
+        frames += 1;
+    }
+
+    assert_eq!(total, CHUNK * CHUNKS);
+    assert!(frames > 1);
+    assert!(largest < total);
+
+    server.shutdown().await;
+}
+
+#[tokio::test]
+async fn get_stream_reports_error_statuses() {
+    let server = TestClient::start(app()).await.unwrap();
+
+    let res = Client::new()
+        .get_stream(&server.url("/missing"), &None)
+        .await
+        .unwrap();
+    assert_eq!(res.status, 404);
+    assert!(!res.is_success());
+
+    server.shutdown().await;
+}
+
+#[tokio::test]
+async fn send_stream_uploads_chunks_and_returns_status() {
+    let server = TestClient::start(app()).await.unwrap();
+
+    let chunks = stream::iter((0..16).map(|_| Ok(Bytes::from(vec![0u8; CHUNK]))));
+    let res = Client::new()
+        .send_stream(hyper::Method::POST, &server.url("/count"), chunks, &None)
+        .await
+        .unwrap();
+    assert_eq!(res.status, 201);
+
+    let body: Vec<Bytes> = res.body.map(Result::unwrap).collect().await;
+    assert_eq!(body.concat(), (CHUNK * 16).to_string().as_bytes());
+
+    server.shutdown().await;
+}
+
+fn disposition(value: &str) -> ClientResponse {
+    let mut headers = hyper::HeaderMap::new();
+    headers.insert("content-disposition", value.parse().unwrap());
+    ClientResponse {
+        status: 200,
+        headers,
+        body: Bytes::new(),
+    }
+}
+
+#[test]
+fn filename_reads_the_plain_parameter() {
+    let res = disposition("attachment; filename=\"report.pdf\"");
+    assert_eq!(res.filename().as_deref(), Some("report.pdf"));
+
+    let res = disposition("attachment; filename=report.pdf");
+    assert_eq!(res.filename().as_deref(), Some("report.pdf"));
+}
+
+#[test]
+fn filename_prefers_the_extended_parameter() {
+    let res =
+        disposition("attachment; filename=\"fallback.txt\"; filename*=UTF-8''r%C3%A9sum%C3%A9.txt");
+    assert_eq!(res.filename().as_deref(), Some("résumé.txt"));
+}
+
+#[test]
+fn filename_strips_path_components() {
+    let res = disposition("attachment; filename=\"../x\"");
+    assert_eq!(res.filename().as_deref(), Some("x"));
+
+    let res = disposition("attachment; filename=\"a\\b\"");
+    assert_eq!(res.filename().as_deref(), Some("b"));
+
+    let res = disposition("attachment; filename=\"../\"");
+    assert_eq!(res.filename(), None);
+}
+
+#[tokio::test]
+async fn head_returns_the_status_without_reading_a_body() {
+    let server = TestClient::start(app()).await.unwrap();
+    let client = Client::new();
+
+    // A GET of `/large` streams 8 MiB; HEAD must finish without waiting for any of it.
+    let status = tokio::time::timeout(
+        std::time::Duration::from_secs(5),
+        client.head(&server.url("/large"), &None),
+    )
+    .await
+    .expect("HEAD waited for a body")
+    .unwrap();
+    assert_eq!(status, 200);
+
+    let status = client.head(&server.url("/missing"), &None).await.unwrap();
+    assert_eq!(status, 404);
+
+    server.shutdown().await;
+}
+
+#[tokio::test]
+async fn stream_channel_sends_chunks_in_order() {
+    let server = TestClient::start(app()).await.unwrap();
+
+    let res = Client::new()
+        .get_stream(&server.url("/chunks"), &None)
+        .await
+        .unwrap();
+    assert_eq!(res.status, 200);
+
+    let body: Vec<Bytes> = res.body.map(Result::unwrap).collect().await;
+    assert_eq!(body.concat(), b"one\ntwo\nthree\n");
+
+    server.shutdown().await;
+}
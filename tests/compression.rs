@@ -0,0 +1,62 @@
+#![cfg(feature = "testing")]
+
+use std::io::Read;
+
+use bolt_web::{
+    App, http::StatusCode, middleware::Compression, request::RequestBody, response::ResponseWriter,
+    testing::TestClient,
+};
+
+async fn partial(_: &mut RequestBody, res: &mut ResponseWriter) {
+    res.status(StatusCode::PartialContent)
+        .set_header("Content-Range", "bytes 0-6143/100000")
+        .text(&"a".repeat(6144));
+}
+
+async fn huge(_: &mut RequestBody, res: &mut ResponseWriter) {
+    res.text(&"compress me ".repeat(16 * 1024));
+}
+
+fn app() -> App {
+    let mut app = App::new();
+    app.set_banner(false);
+    app.middleware("/", None, Compression::new());
+    app.get("/partial", partial);
+    app.get("/huge", huge);
+    app
+}
+
+async fn get_gzip(client: &TestClient, path: &str) -> bolt_web::client::ClientResponse {
+    client
+        .request(hyper::Method::GET, path, &[("accept-encoding", "gzip")], "")
+        .await
+        .unwrap()
+}
+
+#[tokio::test]
+async fn partial_content_is_never_compressed() {
+    let client = TestClient::start(app()).await.unwrap();
+
+    let res = get_gzip(&client, "/partial").await;
+    assert_eq!(res.status, 206);
+    assert!(res.headers.get("content-encoding").is_none());
+    assert_eq!(res.body.len(), 6144);
+
+    client.shutdown().await;
+}
+
+#[tokio::test]
+async fn large_bodies_are_compressed_off_the_worker() {
+    let client = TestClient::start(app()).await.unwrap();
+
+    let res = get_gzip(&client, "/huge").await;
+    assert_eq!(res.headers["content-encoding"], "gzip");
+
+    let mut decoded = String::new();
+    flate2::read::GzDecoder::new(&res.body[..])
+        .read_to_string(&mut decoded)
+        .unwrap();
+    assert_eq!(decoded, "compress me ".repeat(16 * 1024));
+
+    client.shutdown().await;
+}
@@ -0,0 +1,92 @@
+#![cfg(feature = "testing")]
+
+use bolt_web::{
+    App, ProblemJson, async_trait::async_trait, http::StatusCode, request::RequestBody,
+    response::ResponseWriter, testing::TestClient, types::ErrorHandler,
+};
+use serde::{Serialize, Serializer};
+
+struct Unserializable;
+
+impl Serialize for Unserializable {
+    fn serialize<S: Serializer>(&self, _: S) -> Result<S::Ok, S::Error> {
+        Err(serde::ser::Error::custom("cannot serialize"))
+    }
+}
+
+struct BrokenErrors;
+
+#[async_trait]
+impl ErrorHandler for BrokenErrors {
+    async fn run(&self, _msg: String, res: &mut ResponseWriter) {
+        res.json(&Unserializable);
+    }
+}
+
+async fn broken_json(_: &mut RequestBody, res: &mut ResponseWriter) {
+    res.json(&Unserializable);
+}
+
+async fn failing(_: &mut RequestBody, res: &mut ResponseWriter) {
+    res.error(StatusCode::BadRequest, "bad input");
+}
+
+fn assert_fallback(status: u16, body: &[u8]) {
+    assert_eq!(status, 500);
+    let body: serde_json::Value = serde_json::from_slice(body).expect("fallback body is JSON");
+    assert_eq!(body["status"], 500);
+    assert_eq!(body["message"], "Internal Server Error");
+}
+
+#[tokio::test]
+async fn failed_serialization_falls_back_to_static_500() {
+    let mut app = App::new();
+    app.set_banner(false);
+    app.get("/broken", broken_json);
+
+    let client = TestClient::start(app).await.unwrap();
+
+    let res = client.get("/broken").await.unwrap();
+    assert_fallback(res.status, &res.body);
+    assert_eq!(res.headers["content-type"], "application/json");
+
+    client.shutdown().await;
+}
+
+#[tokio::test]
+async fn error_handler_that_cannot_serialize_still_answers() {
+    let mut app = App::new();
+    app.set_banner(false);
+    app.set_error_handler(BrokenErrors);
+    app.get("/failing", failing);
+
+    let client = TestClient::start(app).await.unwrap();
+
+    let res = client.get("/failing").await.unwrap();
+    assert_fallback(res.status, &res.body);
+
+    client.shutdown().await;
+}
+
+#[tokio::test]
+async fn problem_json_describes_a_404() {
+    let mut app = App::new();
+    app.set_banner(false);
+    app.set_error_handler(ProblemJson);
+    app.get("/failing", failing);
+
+    let client = TestClient::start(app).await.unwrap();
+
+    let res = client.get("/missing").await.unwrap();
+    assert_eq!(res.status, 404);
+    assert_eq!(res.headers["content-type"], "application/problem+json");
+
+    let body: serde_json::Value = serde_json::from_slice(&res.body).unwrap();
+    assert_eq!(body["type"], "about:blank");
+    assert_eq!(body["title"], "Not Found");
+    assert_eq!(body["status"], 404);
+    assert_eq!(body["detail"], "Not Found GET /missing");
+    assert!(body.get("instance").is_none());
+
+    client.shutdown().await;
+}
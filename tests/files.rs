@@ -0,0 +1,285 @@
+#![cfg(feature = "testing")]
+
+use std::{
+    io::Read,
+    path::PathBuf,
+    sync::OnceLock,
+    time::{Duration, SystemTime},
+};
+
+use bolt_web::{
+    App,
+    request::RequestBody,
+    response::{EmbeddedAssets, FileOptions, ResponseWriter},
+    testing::TestClient,
+};
+
+const CONTENTS: &[u8] = b"0123456789abcdefghijklmnopqrstuvwxyz";
+
+fn fixture_dir() -> PathBuf {
+    static DIR: OnceLock<PathBuf> = OnceLock::new();
+
+    DIR.get_or_init(|| {
+        let dir = std::env::temp_dir().join(format!("bolt_files_test_{}", std::process::id()));
+        std::fs::create_dir_all(&dir).unwrap();
+        std::fs::write(dir.join("data.bin"), CONTENTS).unwrap();
+        dir
+    })
+    .clone()
+}
+
+async fn plain(_: &mut RequestBody, res: &mut ResponseWriter) {
+    res.file(fixture_dir().join("data.bin")).await;
+}
+
+async fn served(req: &mut RequestBody, res: &mut ResponseWriter) {
+    res.serve_file(req, fixture_dir().join("data.bin"), FileOptions::default())
+        .await;
+}
+
+async fn notes(req: &mut RequestBody, res: &mut ResponseWriter) {
+    res.serve_file(req, fixture_dir().join("notes.txt"), FileOptions::default())
+        .await;
+}
+
+async fn embedded(req: &mut RequestBody, res: &mut ResponseWriter) {
+    static ASSETS: OnceLock<EmbeddedAssets> = OnceLock::new();
+    let assets = ASSETS.get_or_init(|| EmbeddedAssets::new(&[("data.bin", CONTENTS)]));
+
+    res.serve_asset(req, assets, "data.bin", FileOptions::default());
+}
+
+fn app() -> App {
+    let mut app = App::new();
+    app.set_banner(false);
+    app.get("/plain", plain);
+    app.get("/served", served);
+    app.get("/notes", notes);
+    app.get("/embedded", embedded);
+    app.static_dir("/static", fixture_dir().to_str().unwrap());
+    app
+}
+
+#[tokio::test]
+async fn only_serve_file_advertises_ranges() {
+    let client = TestClient::start(app()).await.unwrap();
+
+    let res = client.get("/plain").await.unwrap();
+    assert_eq!(res.status, 200);
+    assert_eq!(res.body, CONTENTS);
+    assert!(res.headers.get("accept-ranges").is_none());
+
+    let res = client.get("/static/data.bin").await.unwrap();
+    assert_eq!(res.status, 200);
+    assert_eq!(res.headers["accept-ranges"], "bytes");
+
+    client.shutdown().await;
+}
+
+async fn get_with(
+    client: &TestClient,
+    headers: &[(&str, &str)],
+) -> bolt_web::client::ClientResponse {
+    client
+        .request(hyper::Method::GET, "/served", headers, "")
+        .await
+        .unwrap()
+}
+
+#[tokio::test]
+async fn full_get_sends_validators_and_length() {
+    let client = TestClient::start(app()).await.unwrap();
+
+    let res = get_with(&client, &[]).await;
+    assert_eq!(res.status, 200);
+    assert_eq!(res.body, CONTENTS);
+    assert_eq!(
+        res.headers["content-length"],
+        CONTENTS.len().to_string().as_str()
+    );
+    assert!(res.headers.contains_key("etag"));
+    assert!(res.headers.contains_key("last-modified"));
+
+    client.shutdown().await;
+}
+
+#[tokio::test]
+async fn matching_validators_get_not_modified() {
+    let client = TestClient::start(app()).await.unwrap();
+
+    let full = get_with(&client, &[]).await;
+    let etag = full.headers["etag"].to_str().unwrap();
+    let modified = full.headers["last-modified"].to_str().unwrap();
+
+    let res = get_with(&client, &[("if-none-match", etag)]).await;
+    assert_eq!(res.status, 304);
+    assert!(res.body.is_empty());
+
+    let res = get_with(&client, &[("if-modified-since", modified)]).await;
+    assert_eq!(res.status, 304);
+    assert!(res.body.is_empty());
+
+    let res = get_with(&client, &[("if-none-match", "\"stale\"")]).await;
+    assert_eq!(res.status, 200);
+
+    client.shutdown().await;
+}
+
+#[tokio::test]
+async fn single_range_gets_partial_content() {
+    let client = TestClient::start(app()).await.unwrap();
+
+    let res = get_with(&client, &[("range", "bytes=2-5")]).await;
+    assert_eq!(res.status, 206);
+    assert_eq!(res.body, "2345");
+    assert_eq!(res.headers["content-range"], "bytes 2-5/36");
+    assert_eq!(res.headers["content-length"], "4");
+
+    let res = get_with(&client, &[("range", "bytes=-3")]).await;
+    assert_eq!(res.status, 206);
+    assert_eq!(res.body, "xyz");
+
+    client.shutdown().await;
+}
+
+#[tokio::test]
+async fn huge_range_end_is_clamped_to_the_file() {
+    let client = TestClient::start(app()).await.unwrap();
+
+    let res = get_with(&client, &[("range", "bytes=0-18446744073709551615")]).await;
+    assert_eq!(res.status, 206);
+    assert_eq!(res.body, CONTENTS);
+    assert_eq!(res.headers["content-range"], "bytes 0-35/36");
+
+    let res = get_embedded(&client, &[("range", "bytes=30-18446744073709551615")]).await;
+    assert_eq!(res.status, 206);
+    assert_eq!(res.body, "uvwxyz");
+
+    client.shutdown().await;
+}
+
+#[tokio::test]
+async fn unsatisfiable_range_gets_416() {
+    let client = TestClient::start(app()).await.unwrap();
+
+    let res = get_with(&client, &[("range", "bytes=100-200")]).await;
+    assert_eq!(res.status, 416);
+    assert_eq!(res.headers["content-range"], "bytes */36");
+
+    client.shutdown().await;
+}
+
+#[tokio::test]
+async fn stale_if_range_falls_back_to_full_body() {
+    let client = TestClient::start(app()).await.unwrap();
+
+    let res = get_with(
+        &client,
+        &[("range", "bytes=2-5"), ("if-range", "\"stale\"")],
+    )
+    .await;
+    assert_eq!(res.status, 200);
+    assert_eq!(res.body, CONTENTS);
+
+    client.shutdown().await;
+}
+
+#[tokio::test]
+async fn head_sends_headers_without_body() {
+    let client = TestClient::start(app()).await.unwrap();
+
+    let res = client
+        .request(hyper::Method::HEAD, "/served", &[], "")
+        .await
+        .unwrap();
+    assert_eq!(res.status, 200);
+    assert!(res.body.is_empty());
+    assert_eq!(
+        res.headers["content-length"],
+        CONTENTS.len().to_string().as_str()
+    );
+    assert!(res.headers.contains_key("etag"));
+
+    client.shutdown().await;
+}
+
+async fn get_embedded(
+    client: &TestClient,
+    headers: &[(&str, &str)],
+) -> bolt_web::client::ClientResponse {
+    client
+        .request(hyper::Method::GET, "/embedded", headers, "")
+        .await
+        .unwrap()
+}
+
+#[tokio::test]
+async fn embedded_asset_serves_ranges() {
+    let client = TestClient::start(app()).await.unwrap();
+
+    let res = get_embedded(&client, &[("range", "bytes=10-15")]).await;
+    assert_eq!(res.status, 206);
+    assert_eq!(res.body, "abcdef");
+    assert_eq!(res.headers["content-range"], "bytes 10-15/36");
+    assert_eq!(res.headers["content-length"], "6");
+
+    client.shutdown().await;
+}
+
+#[tokio::test]
+async fn embedded_asset_honours_if_none_match() {
+    let client = TestClient::start(app()).await.unwrap();
+
+    let full = get_embedded(&client, &[]).await;
+    assert_eq!(full.status, 200);
+    assert_eq!(full.body, CONTENTS);
+    let etag = full.headers["etag"].to_str().unwrap();
+
+    let res = get_embedded(&client, &[("if-none-match", etag)]).await;
+    assert_eq!(res.status, 304);
+    assert!(res.body.is_empty());
+
+    client.shutdown().await;
+}
+
+async fn get_gzip_notes(client: &TestClient) -> String {
+    let res = client
+        .request(
+            hyper::Method::GET,
+            "/notes",
+            &[("accept-encoding", "gzip")],
+            "",
+        )
+        .await
+        .unwrap();
+    assert_eq!(res.headers["content-encoding"], "gzip");
+
+    let mut decoded = String::new();
+    flate2::read::GzDecoder::new(&res.body[..])
+        .read_to_string(&mut decoded)
+        .unwrap();
+    decoded
+}
+
+#[tokio::test]
+async fn compressed_cache_is_invalidated_when_the_file_changes() {
+    let path = fixture_dir().join("notes.txt");
+    std::fs::write(&path, "first draft").unwrap();
+    let client = TestClient::start(app()).await.unwrap();
+
+    assert_eq!(get_gzip_notes(&client).await, "first draft");
+    assert_eq!(get_gzip_notes(&client).await, "first draft");
+
+    std::fs::write(&path, "second draft").unwrap();
+    // Make sure the rewrite is visible even on filesystems with coarse timestamps.
+    std::fs::File::options()
+        .write(true)
+        .open(&path)
+        .unwrap()
+        .set_modified(SystemTime::now() + Duration::from_secs(10))
+        .unwrap();
+
+    assert_eq!(get_gzip_notes(&client).await, "second draft");
+
+    client.shutdown().await;
+}
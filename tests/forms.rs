@@ -0,0 +1,316 @@
+#![cfg(feature = "testing")]
+
+use bolt_web::{
+    App,
+    request::RequestBody,
+    response::ResponseWriter,
+    testing::TestClient,
+    types::{BoltError, FormDataConfig},
+};
+
+const BOUNDARY: &str = "bolt-test-boundary";
+
+async fn upload(req: &mut RequestBody, res: &mut ResponseWriter) -> Result<(), BoltError> {
+    let form = req.form_data().await?;
+    let mut sizes = vec![];
+    for file in &form.files {
+        sizes.push(
+            tokio::fs::metadata(&file.temp_path)
+                .await?
+                .len()
+                .to_string(),
+        );
+    }
+
+    req.cleanup().await;
+    res.text(&format!(
+        "files={} sizes={}",
+        form.files.len(),
+        sizes.join(",")
+    ));
+    Ok(())
+}
+
+async fn echo(req: &mut RequestBody, res: &mut ResponseWriter) -> Result<(), BoltError> {
+    let body = req.bytes().await?;
+    res.text(&body.len().to_string());
+    Ok(())
+}
+
+fn limited_app(limit: Option<usize>) -> App {
+    let mut app = App::new();
+    app.set_banner(false);
+    app.set_body_limit(limit);
+    app.post("/upload", upload);
+    app.post("/echo", echo);
+    app
+}
+
+async fn strict(req: &mut RequestBody, res: &mut ResponseWriter) -> Result<(), BoltError> {
+    let config = FormDataConfig {
+        max_parts: Some(1),
+        ..Default::default()
+    };
+    let form = req.form_data_with(&config).await?;
+    res.text(&format!("parts={}", form.fields.len() + form.files.len()));
+    Ok(())
+}
+
+fn parts_app(max_parts: usize, temp_dir: &std::path::Path) -> App {
+    let mut app = App::new();
+    app.set_banner(false);
+    app.set_form_data_config(FormDataConfig {
+        max_parts: Some(max_parts),
+        temp_dir: Some(temp_dir.to_path_buf()),
+        ..Default::default()
+    });
+    app.post("/upload", upload);
+    app.post("/strict", strict);
+    app
+}
+
+fn file_part(name: &str, contents: &[u8]) -> Vec<u8> {
+    let mut part = format!(
+        "--{BOUNDARY}\r\nContent-Disposition: form-data; name=\"{name}\"; filename=\"{name}.bin\"\r\nContent-Type: application/octet-stream\r\n\r\n"
+    )
+    .into_bytes();
+    part.extend_from_slice(contents);
+    part.extend_from_slice(b"\r\n");
+    part
+}
+
+fn text_part(name: &str, value: &str) -> Vec<u8> {
+    format!("--{BOUNDARY}\r\nContent-Disposition: form-data; name=\"{name}\"\r\n\r\n{value}\r\n")
+        .into_bytes()
+}
+
+fn multipart(parts: &[Vec<u8>]) -> Vec<u8> {
+    let mut body = parts.concat();
+    body.extend_from_slice(format!("--{BOUNDARY}--\r\n").as_bytes());
+    body
+}
+
+async fn post_form(client: &TestClient, body: Vec<u8>) -> (u16, String) {
+    post_form_to(client, "/upload", body).await
+}
+
+async fn post_form_to(client: &TestClient, path: &str, body: Vec<u8>) -> (u16, String) {
+    let content_type = format!("multipart/form-data; boundary={BOUNDARY}");
+    let res = client
+        .request(
+            hyper::Method::POST,
+            path,
+            &[("content-type", content_type.as_str())],
+            body,
+        )
+        .await
+        .unwrap();
+
+    (res.status, String::from_utf8_lossy(&res.body).into_owned())
+}
+
+#[tokio::test]
+async fn uploads_are_exempt_from_the_body_limit() {
+    let client = TestClient::start(limited_app(Some(1024))).await.unwrap();
+
+    let (status, body) = post_form(&client, multipart(&[file_part("doc", &[7; 8192])])).await;
+    assert_eq!(status, 200);
+    assert_eq!(body, "files=1 sizes=8192");
+
+    let res = client.post("/echo", vec![0u8; 8192]).await.unwrap();
+    assert_eq!(res.status, 413);
+
+    client.shutdown().await;
+}
+
+#[tokio::test]
+async fn form_text_fields_still_count_against_the_body_limit() {
+    let client = TestClient::start(limited_app(Some(1024))).await.unwrap();
+
+    let (status, _) = post_form(&client, multipart(&[text_part("note", &"x".repeat(4096))])).await;
+    assert_eq!(status, 413);
+
+    client.shutdown().await;
+}
+
+#[tokio::test]
+async fn body_limit_can_be_disabled() {
+    let client = TestClient::start(limited_app(None)).await.unwrap();
+
+    let res = client
+        .post("/echo", vec![0u8; 4 * 1024 * 1024])
+        .await
+        .unwrap();
+    assert_eq!(res.status, 200);
+    assert_eq!(res.body, "4194304");
+
+    client.shutdown().await;
+}
+
+#[tokio::test]
+async fn content_type_limit_only_applies_to_its_media_type() {
+    let mut app = limited_app(Some(64 * 1024));
+    app.set_content_type_limit("application/json", 1024);
+    let client = TestClient::start(app).await.unwrap();
+
+    for (content_type, status) in [
+        ("application/json; charset=utf-8", 413),
+        ("text/plain", 200),
+    ] {
+        let res = client
+            .request(
+                hyper::Method::POST,
+                "/echo",
+                &[("content-type", content_type)],
+                vec![b' '; 4096],
+            )
+            .await
+            .unwrap();
+        assert_eq!(res.status, status, "{}", content_type);
+    }
+
+    client.shutdown().await;
+}
+
+fn temp_dir(name: &str) -> std::path::PathBuf {
+    let dir = std::env::temp_dir().join(format!("bolt-forms-{}-{}", name, std::process::id()));
+    std::fs::create_dir_all(&dir).unwrap();
+    dir
+}
+
+#[tokio::test]
+async fn part_count_under_the_limit_is_accepted() {
+    let dir = temp_dir("under");
+    let client = TestClient::start(parts_app(3, &dir)).await.unwrap();
+
+    let body = multipart(&[
+        text_part("title", "report"),
+        file_part("a", b"one"),
+        file_part("b", b"two"),
+    ]);
+    let (status, body) = post_form(&client, body).await;
+    assert_eq!(status, 200);
+    assert_eq!(body, "files=2 sizes=3,3");
+
+    client.shutdown().await;
+    std::fs::remove_dir_all(dir).unwrap();
+}
+
+#[tokio::test]
+async fn part_count_over_the_limit_is_rejected_and_cleaned_up() {
+    let dir = temp_dir("over");
+    let client = TestClient::start(parts_app(3, &dir)).await.unwrap();
+
+    let parts: Vec<_> = (0..4)
+        .map(|i| file_part(&format!("f{i}"), b"data"))
+        .collect();
+    let (status, body) = post_form(&client, multipart(&parts)).await;
+    assert_eq!(status, 413);
+    assert!(body.contains("3 parts"), "{}", body);
+    assert_eq!(std::fs::read_dir(&dir).unwrap().count(), 0);
+
+    client.shutdown().await;
+    std::fs::remove_dir_all(dir).unwrap();
+}
+
+#[tokio::test]
+async fn form_data_with_overrides_the_part_limit() {
+    let dir = temp_dir("override");
+    let client = TestClient::start(parts_app(3, &dir)).await.unwrap();
+
+    let body = multipart(&[text_part("a", "1"), text_part("b", "2")]);
+    let (status, _) = post_form_to(&client, "/strict", body).await;
+    assert_eq!(status, 413);
+
+    let (status, _) = post_form_to(&client, "/strict", multipart(&[text_part("a", "1")])).await;
+    assert_eq!(status, 200);
+
+    client.shutdown().await;
+    std::fs::remove_dir_all(dir).unwrap();
+}
+
+#[tokio::test]
+async fn oversized_upload_is_rejected_by_default() {
+    let dir = temp_dir("oversized");
+    let client = TestClient::start(parts_app(3, &dir)).await.unwrap();
+
+    let big = vec![7; 10 * 1024 * 1024 + 1];
+    let (status, body) = post_form(&client, multipart(&[file_part("big", &big)])).await;
+    assert_eq!(status, 413);
+    assert!(body.contains("10485760 bytes"), "{}", body);
+    assert_eq!(std::fs::read_dir(&dir).unwrap().count(), 0);
+
+    client.shutdown().await;
+    std::fs::remove_dir_all(dir).unwrap();
+}
+
+#[cfg(unix)]
+async fn upload_mode(req: &mut RequestBody, res: &mut ResponseWriter) -> Result<(), BoltError> {
+    use std::os::unix::fs::PermissionsExt;
+
+    let form = req.form_data().await?;
+    let mode = tokio::fs::metadata(&form.files[0].temp_path)
+        .await?
+        .permissions()
+        .mode();
+
+    req.cleanup().await;
+    res.text(&format!("{:o}", mode & 0o777));
+    Ok(())
+}
+
+#[cfg(unix)]
+#[tokio::test]
+async fn uploads_are_only_readable_by_the_owner() {
+    let dir = temp_dir("mode");
+    let mut app = parts_app(3, &dir);
+    app.post("/mode", upload_mode);
+
+    let client = TestClient::start(app).await.unwrap();
+
+    let (status, body) = post_form_to(&client, "/mode", multipart(&[file_part("a", b"one")])).await;
+    assert_eq!(status, 200);
+    assert_eq!(body, "600");
+
+    client.shutdown().await;
+    std::fs::remove_dir_all(dir).unwrap();
+}
+
+#[cfg(unix)]
+#[tokio::test]
+async fn startup_sweep_removes_old_orphaned_uploads() {
+    use std::time::{Duration, SystemTime};
+
+    let dir = temp_dir("sweep");
+    let old = dir.join("bolt_upload_old.bin");
+    let fresh = dir.join("bolt_upload_fresh.bin");
+    let unrelated = dir.join("notes.txt");
+
+    for path in [&old, &fresh, &unrelated] {
+        std::fs::write(path, b"data").unwrap();
+    }
+    let two_hours_ago = SystemTime::now() - Duration::from_secs(2 * 60 * 60);
+    for path in [&old, &unrelated] {
+        std::fs::File::options()
+            .write(true)
+            .open(path)
+            .unwrap()
+            .set_modified(two_hours_ago)
+            .unwrap();
+    }
+
+    let mut app = parts_app(3, &dir);
+    app.set_upload_orphan_ttl(Duration::from_secs(60 * 60));
+    let client = TestClient::start(app).await.unwrap();
+
+    // The sweep finishes before the first connection is accepted.
+    let (status, _) = post_form(&client, multipart(&[text_part("a", "1")])).await;
+    assert_eq!(status, 200);
+
+    assert!(!old.exists());
+    assert!(fresh.exists());
+    assert!(unrelated.exists());
+
+    client.shutdown().await;
+    std::fs::remove_dir_all(dir).unwrap();
+}
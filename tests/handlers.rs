@@ -0,0 +1,93 @@
+#![cfg(feature = "testing")]
+
+use std::sync::{
+    Arc,
+    atomic::{AtomicUsize, Ordering},
+};
+
+use bolt_web::{
+    App, blocking, request::RequestBody, response::ResponseWriter, testing::TestClient,
+    types::BoltError,
+};
+
+#[tokio::test]
+async fn closure_handlers_are_served_and_capture_state() {
+    let hits = Arc::new(AtomicUsize::new(0));
+
+    let mut app = App::new();
+    app.set_banner(false);
+
+    let counter = hits.clone();
+    app.get_fn("/count", move |_req, res| {
+        let n = counter.fetch_add(1, Ordering::SeqCst) + 1;
+        Box::pin(async move {
+            res.text(&n.to_string());
+        })
+    });
+
+    app.post_fn("/echo/:name", |req, res| {
+        Box::pin(async move {
+            let body = req.text().await.unwrap_or_default();
+            res.text(&format!("{} {}", req.param("name"), body));
+        })
+    });
+
+    let client = TestClient::start(app).await.unwrap();
+
+    assert_eq!(client.get("/count").await.unwrap().body, "1");
+    assert_eq!(client.get("/count").await.unwrap().body, "2");
+    assert_eq!(hits.load(Ordering::SeqCst), 2);
+
+    let res = client.post("/echo/bolt", "hi").await.unwrap();
+    assert_eq!(res.status, 200);
+    assert_eq!(res.body, "bolt hi");
+
+    client.shutdown().await;
+}
+
+async fn square(req: &mut RequestBody, res: &mut ResponseWriter) -> Result<(), BoltError> {
+    let n: u64 = req.param_as("n")?;
+    let squared = blocking(move || n * n).await?;
+    res.text(&squared.to_string());
+    Ok(())
+}
+
+async fn parse(req: &mut RequestBody, res: &mut ResponseWriter) -> Result<(), BoltError> {
+    let input = req.param("input");
+    let n = blocking(move || input.parse::<u64>()).await??;
+    res.text(&n.to_string());
+    Ok(())
+}
+
+async fn explode(_: &mut RequestBody, res: &mut ResponseWriter) -> Result<(), BoltError> {
+    blocking(|| panic!("boom")).await?;
+    res.text("unreachable");
+    Ok(())
+}
+
+#[tokio::test]
+async fn blocking_returns_results_and_propagates_errors() {
+    let mut app = App::new();
+    app.set_banner(false);
+    app.get("/square/:n", square);
+    app.get("/parse/:input", parse);
+    app.get("/explode", explode);
+
+    let client = TestClient::start(app).await.unwrap();
+
+    let res = client.get("/square/12").await.unwrap();
+    assert_eq!(res.status, 200);
+    assert_eq!(res.body, "144");
+
+    let res = client.get("/parse/42").await.unwrap();
+    assert_eq!(res.body, "42");
+
+    let res = client.get("/parse/nope").await.unwrap();
+    assert_eq!(res.status, 500);
+
+    let res = client.get("/explode").await.unwrap();
+    assert_eq!(res.status, 500);
+    assert_ne!(res.body, "unreachable");
+
+    client.shutdown().await;
+}
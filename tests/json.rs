@@ -0,0 +1,65 @@
+#![cfg(feature = "testing")]
+
+use bolt_web::{
+    App, http::StatusCode, request::RequestBody, response::ResponseWriter, testing::TestClient,
+};
+use serde_json::{Value, json};
+
+async fn signed(req: &mut RequestBody, res: &mut ResponseWriter) {
+    match req.json_with_raw::<Value>().await {
+        Ok((value, raw)) => {
+            res.json(&json!({
+                "value": value,
+                "raw": String::from_utf8(raw.to_vec()).unwrap(),
+            }));
+        }
+        Err(e) => {
+            res.status(StatusCode::BadRequest).text(&e.to_string());
+        }
+    }
+}
+
+fn app() -> App {
+    let mut app = App::new();
+    app.set_banner(false);
+    app.post("/signed", signed);
+    app
+}
+
+async fn post_json(client: &TestClient, body: &str) -> bolt_web::client::ClientResponse {
+    client
+        .request(
+            hyper::Method::POST,
+            "/signed",
+            &[("content-type", "application/json")],
+            body.to_string(),
+        )
+        .await
+        .unwrap()
+}
+
+#[tokio::test]
+async fn json_with_raw_returns_the_value_and_the_exact_bytes() {
+    let client = TestClient::start(app()).await.unwrap();
+
+    let body = "{ \"amount\" : 42,\n  \"note\": \"hi\" }";
+    let res = post_json(&client, body).await;
+    assert_eq!(res.status, 200);
+
+    let echoed: Value = serde_json::from_slice(&res.body).unwrap();
+    assert_eq!(echoed["value"], json!({"amount": 42, "note": "hi"}));
+    assert_eq!(echoed["raw"], body);
+
+    client.shutdown().await;
+}
+
+#[tokio::test]
+async fn json_with_raw_rejects_malformed_bodies() {
+    let client = TestClient::start(app()).await.unwrap();
+
+    let res = post_json(&client, "{\"amount\": ").await;
+    assert_eq!(res.status, 400);
+    assert!(!res.body.is_empty());
+
+    client.shutdown().await;
+}
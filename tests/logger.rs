@@ -0,0 +1,194 @@
+#![cfg(feature = "testing")]
+
+use std::{
+    io,
+    sync::{
+        Arc, Mutex,
+        atomic::{AtomicUsize, Ordering},
+    },
+};
+
+use bolt_web::{
+    App,
+    async_trait::async_trait,
+    middleware::{Compression, Logger},
+    request::RequestBody,
+    response::ResponseWriter,
+    testing::TestClient,
+    types::{Middleware, MiddlewareOutcome},
+};
+
+#[derive(Clone, Default)]
+struct Capture(Arc<Mutex<Vec<u8>>>);
+
+impl io::Write for Capture {
+    fn write(&mut self, buf: &[u8]) -> io::Result<usize> {
+        self.0.lock().unwrap().extend_from_slice(buf);
+        Ok(buf.len())
+    }
+
+    fn flush(&mut self) -> io::Result<()> {
+        Ok(())
+    }
+}
+
+impl Capture {
+    fn output(&self) -> String {
+        String::from_utf8_lossy(&self.0.lock().unwrap()).into_owned()
+    }
+}
+
+fn capture() -> (Capture, tracing::subscriber::DefaultGuard) {
+    let capture = Capture::default();
+    let writer = capture.clone();
+    let subscriber = tracing_subscriber::fmt()
+        .with_ansi(false)
+        .with_writer(move || writer.clone())
+        .finish();
+
+    (capture, tracing::subscriber::set_default(subscriber))
+}
+
+async fn hello(_: &mut RequestBody, res: &mut ResponseWriter) {
+    res.text("hello");
+}
+
+#[tokio::test]
+async fn requests_are_logged_as_tracing_events() {
+    let (capture, _guard) = capture();
+
+    let mut app = App::new();
+    app.set_banner(false);
+    app.middleware(
+        "/",
+        None,
+        Logger::new().format("{method} {path} -> {status}"),
+    );
+    app.get("/hello", hello);
+
+    let client = TestClient::start(app).await.unwrap();
+    client.get("/hello").await.unwrap();
+    client.shutdown().await;
+
+    let output = capture.output();
+    let line = output
+        .lines()
+        .find(|l| l.contains("GET /hello -> 200"))
+        .unwrap_or_else(|| panic!("no access log line in {:?}", output));
+
+    assert!(line.contains("INFO"), "{}", line);
+    assert!(line.contains("status=200"), "{}", line);
+    assert!(line.contains("method=\"GET\""), "{}", line);
+    assert!(line.contains("path=\"/hello\""), "{}", line);
+}
+
+async fn large(_: &mut RequestBody, res: &mut ResponseWriter) {
+    res.text(&"compress me ".repeat(512));
+}
+
+#[tokio::test]
+async fn logged_byte_count_is_the_compressed_size() {
+    let (capture, _guard) = capture();
+
+    let mut app = App::new();
+    app.set_banner(false);
+    app.middleware("/", None, Logger::new().format("{path} {bytes}"));
+    app.middleware("/", None, Compression::new());
+    app.get("/large", large);
+
+    let client = TestClient::start(app).await.unwrap();
+    let res = client
+        .request(
+            hyper::Method::GET,
+            "/large",
+            &[("accept-encoding", "gzip")],
+            "",
+        )
+        .await
+        .unwrap();
+    client.shutdown().await;
+
+    assert_eq!(res.headers["content-encoding"], "gzip");
+    assert!(res.body.len() < 12 * 512);
+
+    let output = capture.output();
+    let expected = format!("/large {}", res.body.len());
+    let line = output
+        .lines()
+        .find(|l| l.contains(&expected))
+        .unwrap_or_else(|| panic!("no line with {:?} in {:?}", expected, output));
+
+    assert!(
+        line.contains(&format!("bytes={}", res.body.len())),
+        "{}",
+        line
+    );
+    assert!(line.contains("transformed=true"), "{}", line);
+}
+
+async fn explode(_: &mut RequestBody, _: &mut ResponseWriter) {
+    panic!("db password is hunter2");
+}
+
+#[tokio::test]
+async fn panic_messages_are_logged_but_never_sent() {
+    let (capture, _guard) = capture();
+
+    let mut app = App::new();
+    app.set_banner(false);
+    app.get("/explode", explode);
+
+    let client = TestClient::start(app).await.unwrap();
+    let res = client.get("/explode").await.unwrap();
+    client.shutdown().await;
+
+    assert_eq!(res.status, 500);
+    assert!(!String::from_utf8_lossy(&res.body).contains("hunter2"));
+
+    let output = capture.output();
+    let line = output
+        .lines()
+        .find(|l| l.contains("Handler panicked"))
+        .unwrap_or_else(|| panic!("no panic line in {:?}", output));
+    assert!(line.contains("hunter2"), "{}", line);
+    assert!(line.contains("/explode"), "{}", line);
+}
+
+struct Canned;
+
+#[async_trait]
+impl Middleware for Canned {
+    async fn handle(&self, _req: &mut RequestBody, res: &mut ResponseWriter) -> MiddlewareOutcome {
+        res.text("from middleware");
+        MiddlewareOutcome::Stop
+    }
+}
+
+static CANNED_HANDLER_CALLS: AtomicUsize = AtomicUsize::new(0);
+
+async fn never(_: &mut RequestBody, res: &mut ResponseWriter) {
+    CANNED_HANDLER_CALLS.fetch_add(1, Ordering::SeqCst);
+    res.text("from handler");
+}
+
+#[tokio::test]
+async fn short_circuited_responses_are_still_logged() {
+    let (capture, _guard) = capture();
+
+    let mut app = App::new();
+    app.set_banner(false);
+    app.middleware("/", None, Logger::new().format("{path} {status} {bytes}"));
+    app.middleware("/", None, Canned);
+    app.get("/canned", never);
+
+    let client = TestClient::start(app).await.unwrap();
+    let res = client.get("/canned").await.unwrap();
+    client.shutdown().await;
+
+    assert_eq!(res.status, 200);
+    assert_eq!(res.body, "from middleware");
+    assert_eq!(CANNED_HANDLER_CALLS.load(Ordering::SeqCst), 0);
+
+    let output = capture.output();
+    assert!(output.contains("/canned 200 15"), "{:?}", output);
+}
@@ -0,0 +1,182 @@
+#![cfg(feature = "testing")]
+
+use bolt_web::{
+    App,
+    async_trait::async_trait,
+    http::{HttpError, StatusCode},
+    request::RequestBody,
+    response::ResponseWriter,
+    testing::TestClient,
+    types::{Method, Middleware, MiddlewareOutcome},
+};
+use std::sync::{
+    Arc,
+    atomic::{AtomicUsize, Ordering},
+};
+use tokio::{
+    io::{AsyncReadExt, AsyncWriteExt},
+    net::TcpStream,
+};
+
+struct DenyAll;
+
+#[async_trait]
+impl Middleware for DenyAll {
+    async fn handle(&self, _req: &mut RequestBody, _res: &mut ResponseWriter) -> MiddlewareOutcome {
+        MiddlewareOutcome::Error(HttpError::new(StatusCode::Forbidden, "Forbidden"))
+    }
+}
+
+async fn ok(_: &mut RequestBody, res: &mut ResponseWriter) {
+    res.text("ok");
+}
+
+fn guarded_app() -> App {
+    let mut app = App::new();
+    app.set_banner(false);
+    app.set_allow_trace(true);
+    app.middleware("/", None, DenyAll);
+    app.get("/", ok);
+    app
+}
+
+#[tokio::test]
+async fn method_less_middleware_covers_every_method() {
+    let client = TestClient::start(guarded_app()).await.unwrap();
+
+    for method in [
+        "GET", "HEAD", "POST", "PUT", "PATCH", "DELETE", "OPTIONS", "TRACE",
+    ] {
+        let method = hyper::Method::from_bytes(method.as_bytes()).unwrap();
+        let res = client.request(method.clone(), "/", &[], "").await.unwrap();
+        assert_eq!(res.status, 403, "{}", method);
+    }
+
+    client.shutdown().await;
+}
+
+#[tokio::test]
+async fn method_less_middleware_covers_connect() {
+    let client = TestClient::start(guarded_app()).await.unwrap();
+
+    let mut stream = TcpStream::connect(client.addr()).await.unwrap();
+    stream
+        .write_all(b"CONNECT example.com:443 HTTP/1.1\r\nHost: example.com:443\r\n\r\n")
+        .await
+        .unwrap();
+
+    let mut buf = vec![0; 64];
+    let n = stream.read(&mut buf).await.unwrap();
+    let head = String::from_utf8_lossy(&buf[..n]);
+    assert!(head.starts_with("HTTP/1.1 403"), "{}", head);
+
+    drop(stream);
+    client.shutdown().await;
+}
+
+async fn secret(_: &mut RequestBody, res: &mut ResponseWriter) {
+    res.set_header("X-Secret", "hunter2").text("secret");
+}
+
+#[tokio::test]
+async fn head_passes_through_get_middleware() {
+    let mut app = App::new();
+    app.set_banner(false);
+    app.middleware("/", Some(Method::GET), DenyAll);
+    app.get("/secret", secret);
+    app.post("/secret", secret);
+
+    let client = TestClient::start(app).await.unwrap();
+
+    for method in [hyper::Method::GET, hyper::Method::HEAD] {
+        let res = client
+            .request(method.clone(), "/secret", &[], "")
+            .await
+            .unwrap();
+        assert_eq!(res.status, 403, "{}", method);
+        assert!(res.headers.get("x-secret").is_none(), "{}", method);
+    }
+
+    let res = client.post("/secret", "").await.unwrap();
+    assert_eq!(res.status, 200);
+
+    client.shutdown().await;
+}
+
+struct Count(Arc<AtomicUsize>);
+
+#[async_trait]
+impl Middleware for Count {
+    async fn handle(&self, _req: &mut RequestBody, _res: &mut ResponseWriter) -> MiddlewareOutcome {
+        self.0.fetch_add(1, Ordering::SeqCst);
+        MiddlewareOutcome::Continue
+    }
+}
+
+#[tokio::test]
+async fn method_less_middleware_runs_once_for_head() {
+    let calls = Arc::new(AtomicUsize::new(0));
+
+    let mut app = App::new();
+    app.set_banner(false);
+    app.middleware("/", None, Count(calls.clone()));
+    app.get("/", ok);
+
+    let client = TestClient::start(app).await.unwrap();
+
+    let res = client
+        .request(hyper::Method::HEAD, "/", &[], "")
+        .await
+        .unwrap();
+    assert_eq!(res.status, 200);
+    assert_eq!(calls.load(Ordering::SeqCst), 1);
+
+    client.shutdown().await;
+}
+
+struct Echo;
+
+#[async_trait]
+impl Middleware for Echo {
+    async fn handle(&self, req: &mut RequestBody, res: &mut ResponseWriter) -> MiddlewareOutcome {
+        res.text(&format!("{} {}", req.method(), req.path()));
+        MiddlewareOutcome::Stop
+    }
+}
+
+fn trace_app(allow: bool) -> App {
+    let mut app = App::new();
+    app.set_banner(false);
+    app.set_allow_trace(allow);
+    app.middleware("/", Some(Method::TRACE), Echo);
+    app.get("/", ok);
+    app
+}
+
+#[tokio::test]
+async fn trace_is_rejected_by_default() {
+    let client = TestClient::start(trace_app(false)).await.unwrap();
+
+    let res = client
+        .request(hyper::Method::TRACE, "/", &[], "")
+        .await
+        .unwrap();
+    assert_eq!(res.status, 405);
+    assert_ne!(res.body, "TRACE /");
+
+    client.shutdown().await;
+}
+
+#[tokio::test]
+async fn trace_reaches_the_app_once_allowed() {
+    let client = TestClient::start(trace_app(true)).await.unwrap();
+
+    let res = client
+        .request(hyper::Method::TRACE, "/", &[], "")
+        .await
+        .unwrap();
+    assert_eq!(res.status, 200);
+    assert_eq!(res.body, "TRACE /");
+
+    client.shutdown().await;
+}
@@ -0,0 +1,146 @@
+#![cfg(feature = "testing")]
+
+use std::sync::{
+    Arc,
+    atomic::{AtomicUsize, Ordering},
+};
+
+#[allow(deprecated)]
+use bolt_web::types::RunMiddleware;
+use bolt_web::{
+    App, Middleware,
+    async_trait::async_trait,
+    http::{HttpError, StatusCode},
+    request::RequestBody,
+    response::ResponseWriter,
+    testing::TestClient,
+    types::{LegacyMiddleware, Middleware, MiddlewareOutcome},
+};
+
+struct Gate {
+    after_calls: Arc<AtomicUsize>,
+}
+
+#[async_trait]
+impl Middleware for Gate {
+    async fn handle(&self, req: &mut RequestBody, res: &mut ResponseWriter) -> MiddlewareOutcome {
+        match req.headers().get("x-gate").and_then(|v| v.to_str().ok()) {
+            Some("stop") => {
+                res.status(StatusCode::Accepted).text("stopped");
+                MiddlewareOutcome::Stop
+            }
+            Some("error") => {
+                MiddlewareOutcome::Error(HttpError::new(StatusCode::Forbidden, "gate closed"))
+            }
+            _ => MiddlewareOutcome::Continue,
+        }
+    }
+
+    async fn after(&self, _req: &mut RequestBody, _res: &mut ResponseWriter) {
+        self.after_calls.fetch_add(1, Ordering::SeqCst);
+    }
+}
+
+async fn handler(_: &mut RequestBody, res: &mut ResponseWriter) {
+    res.text("handler");
+}
+
+fn gated_app(after_calls: Arc<AtomicUsize>) -> App {
+    let mut app = App::new();
+    app.set_banner(false);
+    app.middleware("/", None, Gate { after_calls });
+    app.get("/", handler);
+    app
+}
+
+async fn get_with_gate(client: &TestClient, gate: &str) -> (u16, String) {
+    let res = client
+        .request(hyper::Method::GET, "/", &[("x-gate", gate)], "")
+        .await
+        .unwrap();
+    (res.status, String::from_utf8_lossy(&res.body).into_owned())
+}
+
+#[tokio::test]
+async fn outcomes_control_the_pipeline() {
+    let after_calls = Arc::new(AtomicUsize::new(0));
+    let client = TestClient::start(gated_app(after_calls.clone()))
+        .await
+        .unwrap();
+
+    assert_eq!(
+        get_with_gate(&client, "open").await,
+        (200, "handler".to_string())
+    );
+    assert_eq!(
+        get_with_gate(&client, "stop").await,
+        (202, "stopped".to_string())
+    );
+
+    let (status, body) = get_with_gate(&client, "error").await;
+    assert_eq!(status, 403);
+    assert!(body.contains("gate closed"), "{}", body);
+
+    assert_eq!(after_calls.load(Ordering::SeqCst), 3);
+
+    client.shutdown().await;
+}
+
+async fn short_circuit(req: &mut RequestBody, res: &mut ResponseWriter) {
+    if req.path() == "/blocked" {
+        res.status(StatusCode::ServiceUnavailable)
+            .text("maintenance");
+        res.complete();
+    }
+}
+
+#[tokio::test]
+async fn function_middleware_continues_unless_it_completes() {
+    let mut app = App::new();
+    app.set_banner(false);
+    Middleware!(app, "/", short_circuit);
+    app.get("/", handler);
+    app.get("/blocked", handler);
+
+    let client = TestClient::start(app).await.unwrap();
+
+    assert_eq!(client.get("/").await.unwrap().body, "handler");
+
+    let res = client.get("/blocked").await.unwrap();
+    assert_eq!(res.status, 503);
+    assert_eq!(res.body, "maintenance");
+
+    client.shutdown().await;
+}
+
+struct Legacy;
+
+#[allow(deprecated)]
+#[async_trait]
+impl RunMiddleware for Legacy {
+    async fn run(&self, req: &mut RequestBody, res: &mut ResponseWriter) {
+        if req.headers().contains_key("x-deny") {
+            res.error(StatusCode::Forbidden, "denied");
+        }
+    }
+}
+
+#[tokio::test]
+async fn run_style_middleware_still_works() {
+    let mut app = App::new();
+    app.set_banner(false);
+    app.middleware("/", None, LegacyMiddleware(Legacy));
+    app.get("/", handler);
+
+    let client = TestClient::start(app).await.unwrap();
+
+    assert_eq!(client.get("/").await.unwrap().body, "handler");
+
+    let res = client
+        .request(hyper::Method::GET, "/", &[("x-deny", "1")], "")
+        .await
+        .unwrap();
+    assert_eq!(res.status, 403);
+
+    client.shutdown().await;
+}
@@ -0,0 +1,57 @@
+#![cfg(feature = "testing")]
+
+use bolt_web::{App, request::RequestBody, response::ResponseWriter, testing::TestClient};
+
+async fn language(req: &mut RequestBody, res: &mut ResponseWriter) {
+    let supported = req.query().get("supported").cloned().unwrap_or_default();
+    let supported: Vec<&str> = supported.split(',').collect();
+
+    let lang = req.preferred_language(&supported);
+    res.text(lang.as_deref().unwrap_or("none"));
+}
+
+async fn pick(client: &TestClient, supported: &str, accept: &str) -> String {
+    let res = client
+        .request(
+            hyper::Method::GET,
+            &format!("/lang?supported={}", supported),
+            &[("accept-language", accept)],
+            "",
+        )
+        .await
+        .unwrap();
+    String::from_utf8_lossy(&res.body).into_owned()
+}
+
+fn app() -> App {
+    let mut app = App::new();
+    app.set_banner(false);
+    app.get("/lang", language);
+    app
+}
+
+#[tokio::test]
+async fn highest_weighted_supported_language_wins() {
+    let client = TestClient::start(app()).await.unwrap();
+
+    assert_eq!(
+        pick(&client, "en,fr,de", "fr;q=0.5, de;q=0.9, en;q=0.1").await,
+        "de"
+    );
+    assert_eq!(pick(&client, "en,fr", "ja, fr;q=0.2").await, "fr");
+    assert_eq!(pick(&client, "en,fr", "ja").await, "none");
+    assert_eq!(pick(&client, "en,fr", "ja, *;q=0.1").await, "en");
+
+    client.shutdown().await;
+}
+
+#[tokio::test]
+async fn region_tags_match_their_base_language() {
+    let client = TestClient::start(app()).await.unwrap();
+
+    assert_eq!(pick(&client, "fr,en", "en-GB").await, "en");
+    assert_eq!(pick(&client, "en-US,fr", "en").await, "en-US");
+    assert_eq!(pick(&client, "en,en-GB", "en-GB").await, "en-GB");
+
+    client.shutdown().await;
+}
@@ -0,0 +1,91 @@
+#![cfg(feature = "testing")]
+
+use bolt_web::{
+    App, http::StatusCode, request::RequestBody, response::ResponseWriter, testing::TestClient,
+};
+
+async fn item(_: &mut RequestBody, res: &mut ResponseWriter) {
+    res.text("item");
+}
+
+async fn options(req: &mut RequestBody, res: &mut ResponseWriter) {
+    let methods = req.allowed_methods().to_vec();
+    res.status(StatusCode::NoContent).allow(&methods);
+    res.set_header("x-route", req.route().unwrap_or_default());
+}
+
+#[tokio::test]
+async fn options_handler_sets_allow_from_route_methods() {
+    let mut app = App::new();
+    app.set_banner(false);
+    app.get("/items/:id", item);
+    app.put("/items/:id", item);
+    app.delete("/items/:id", item);
+    app.options("/items/:id", options);
+
+    let client = TestClient::start(app).await.unwrap();
+
+    let res = client
+        .request(hyper::Method::OPTIONS, "/items/7", &[], "")
+        .await
+        .unwrap();
+    assert_eq!(res.status, 204);
+    assert_eq!(res.headers["x-route"], "/items/:id");
+
+    let allow = res.headers["allow"].to_str().unwrap();
+    let mut methods: Vec<&str> = allow.split(", ").collect();
+    methods.sort();
+    assert_eq!(methods, ["DELETE", "GET", "HEAD", "OPTIONS", "PUT"]);
+
+    client.shutdown().await;
+}
+
+#[tokio::test]
+async fn options_without_handler_is_answered_by_the_router() {
+    let mut app = App::new();
+    app.set_banner(false);
+    app.get("/items/:id", item);
+    app.post("/items/:id", item);
+
+    let client = TestClient::start(app).await.unwrap();
+
+    let res = client
+        .request(hyper::Method::OPTIONS, "/items/7", &[], "")
+        .await
+        .unwrap();
+    assert_eq!(res.status, 204);
+    assert!(res.headers["allow"].to_str().unwrap().contains("POST"));
+
+    client.shutdown().await;
+}
+
+#[tokio::test]
+async fn method_not_allowed_lists_every_matching_route() {
+    let mut app = App::new();
+    app.set_banner(false);
+    app.post("/items/new", item);
+    app.get("/items/:id", item);
+    app.put("/:kind/new", item);
+    app.delete("/other/:id", item);
+
+    let client = TestClient::start(app).await.unwrap();
+
+    let res = client
+        .request(hyper::Method::PATCH, "/items/new", &[], "")
+        .await
+        .unwrap();
+    assert_eq!(res.status, 405);
+
+    let allow = res.headers["allow"].to_str().unwrap();
+    let mut methods: Vec<&str> = allow.split(", ").collect();
+    methods.sort();
+    assert_eq!(methods, ["GET", "HEAD", "OPTIONS", "POST", "PUT"]);
+
+    let res = client
+        .request(hyper::Method::PATCH, "/nowhere", &[], "")
+        .await
+        .unwrap();
+    assert_eq!(res.status, 404);
+
+    client.shutdown().await;
+}
@@ -0,0 +1,79 @@
+#![cfg(feature = "testing")]
+
+use std::time::Duration;
+
+use bolt_web::{
+    App, request::RequestBody, response::ResponseWriter, testing::TestClient, types::OverloadPolicy,
+};
+use tokio::net::TcpStream;
+
+async fn ok(_: &mut RequestBody, res: &mut ResponseWriter) {
+    res.text("ok");
+}
+
+fn waiting_app() -> App {
+    let mut app = App::new();
+    app.set_banner(false);
+    app.set_connection_limit(1);
+    app.set_overload_policy(OverloadPolicy::Wait(Duration::from_secs(10)));
+    app.set_shutdown_timeout(Duration::from_millis(100));
+    app.get("/", ok);
+    app
+}
+
+fn rejecting_app() -> App {
+    let mut app = App::new();
+    app.set_banner(false);
+    app.set_connection_limit(1);
+    app.set_shutdown_timeout(Duration::from_millis(100));
+    app.get("/", ok);
+    app
+}
+
+#[tokio::test]
+async fn connection_over_the_limit_gets_503_by_default() {
+    let client = TestClient::start(rejecting_app()).await.unwrap();
+
+    let holder = TcpStream::connect(client.addr()).await.unwrap();
+    tokio::time::sleep(Duration::from_millis(50)).await;
+
+    let res = client.get("/").await.unwrap();
+    assert_eq!(res.status, 503);
+    assert_eq!(res.headers["retry-after"], "1");
+
+    drop(holder);
+    client.shutdown().await;
+}
+
+#[tokio::test]
+async fn waiting_connection_is_served_when_a_slot_frees() {
+    let client = TestClient::start(waiting_app()).await.unwrap();
+
+    let holder = TcpStream::connect(client.addr()).await.unwrap();
+    tokio::time::sleep(Duration::from_millis(50)).await;
+
+    let release = tokio::spawn(async move {
+        tokio::time::sleep(Duration::from_millis(200)).await;
+        drop(holder);
+    });
+
+    let res = client.get("/").await.unwrap();
+    assert_eq!(res.status, 200);
+
+    release.await.unwrap();
+    client.shutdown().await;
+}
+
+#[tokio::test]
+async fn waiting_connections_do_not_block_shutdown() {
+    let client = TestClient::start(waiting_app()).await.unwrap();
+
+    let _holder = TcpStream::connect(client.addr()).await.unwrap();
+    tokio::time::sleep(Duration::from_millis(50)).await;
+    let _waiter = TcpStream::connect(client.addr()).await.unwrap();
+    tokio::time::sleep(Duration::from_millis(50)).await;
+
+    tokio::time::timeout(Duration::from_secs(2), client.shutdown())
+        .await
+        .expect("shutdown waited on a queued connection");
+}
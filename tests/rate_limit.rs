@@ -0,0 +1,95 @@
+#![cfg(feature = "testing")]
+
+use std::{net::IpAddr, time::Duration};
+
+use bolt_web::{
+    App, middleware::RateLimiter, request::RequestBody, response::ResponseWriter,
+    testing::TestClient,
+};
+use tokio::{
+    io::{AsyncReadExt, AsyncWriteExt},
+    net::TcpSocket,
+};
+
+async fn ok(_: &mut RequestBody, res: &mut ResponseWriter) {
+    res.text("ok");
+}
+
+fn app(limiter: RateLimiter, proxy: Option<IpAddr>) -> App {
+    let mut app = App::new();
+    if let Some(proxy) = proxy {
+        app.trust_proxy(proxy);
+    }
+    app.set_banner(false);
+    app.middleware("/", None, limiter);
+    app.get("/", ok);
+    app
+}
+
+/// Sends a request from a socket bound to `from`, so the server sees that peer address.
+async fn status_from(client: &TestClient, from: &str) -> u16 {
+    let socket = TcpSocket::new_v4().unwrap();
+    socket.bind(format!("{}:0", from).parse().unwrap()).unwrap();
+    let mut stream = socket.connect(client.addr()).await.unwrap();
+
+    stream
+        .write_all(b"GET / HTTP/1.1\r\nHost: localhost\r\nConnection: close\r\n\r\n")
+        .await
+        .unwrap();
+
+    let mut response = String::new();
+    stream.read_to_string(&mut response).await.unwrap();
+    response[9..12].parse().unwrap()
+}
+
+async fn status_forwarded_for(client: &TestClient, hops: &str) -> u16 {
+    client
+        .request(hyper::Method::GET, "/", &[("x-forwarded-for", hops)], "")
+        .await
+        .unwrap()
+        .status
+}
+
+// Only Linux routes the whole 127.0.0.0/8 block to loopback out of the box.
+#[cfg(target_os = "linux")]
+#[tokio::test]
+async fn different_peers_get_independent_buckets() {
+    let limiter = RateLimiter::new(1, Duration::from_secs(60));
+    let client = TestClient::start(app(limiter, None)).await.unwrap();
+
+    assert_eq!(status_from(&client, "127.0.0.1").await, 200);
+    assert_eq!(status_from(&client, "127.0.0.1").await, 429);
+    assert_eq!(status_from(&client, "127.0.0.2").await, 200);
+    assert_eq!(status_from(&client, "127.0.0.2").await, 429);
+
+    client.shutdown().await;
+}
+
+#[tokio::test]
+async fn forwarded_for_is_ignored_from_untrusted_peers() {
+    let limiter = RateLimiter::new(1, Duration::from_secs(60));
+    let client = TestClient::start(app(limiter, None)).await.unwrap();
+
+    assert_eq!(status_forwarded_for(&client, "203.0.113.1").await, 200);
+    assert_eq!(status_forwarded_for(&client, "203.0.113.2").await, 429);
+
+    client.shutdown().await;
+}
+
+#[tokio::test]
+async fn trusted_proxy_keys_on_the_nearest_untrusted_hop() {
+    let proxy: IpAddr = "127.0.0.1".parse().unwrap();
+    let limiter = RateLimiter::new(1, Duration::from_secs(60));
+    let client = TestClient::start(app(limiter, Some(proxy))).await.unwrap();
+
+    assert_eq!(status_forwarded_for(&client, "203.0.113.1").await, 200);
+    assert_eq!(status_forwarded_for(&client, "203.0.113.1").await, 429);
+    // A client can't escape its bucket by prepending a spoofed hop.
+    assert_eq!(
+        status_forwarded_for(&client, "198.51.100.9, 203.0.113.1").await,
+        429
+    );
+    assert_eq!(status_forwarded_for(&client, "203.0.113.2").await, 200);
+
+    client.shutdown().await;
+}
@@ -0,0 +1,51 @@
+use std::{cell::Cell, time::Duration};
+
+use tokio::time::Instant;
+
+use bolt_web::retry;
+
+#[tokio::test]
+async fn succeeds_on_second_attempt() {
+    let calls = Cell::new(0);
+
+    let result: Result<&str, String> = retry(3, Duration::from_millis(1), || {
+        calls.set(calls.get() + 1);
+        let attempt = calls.get();
+        async move {
+            if attempt < 2 {
+                Err(format!("fail {}", attempt))
+            } else {
+                Ok("done")
+            }
+        }
+    })
+    .await;
+
+    assert_eq!(result, Ok("done"));
+    assert_eq!(calls.get(), 2);
+}
+
+#[tokio::test]
+async fn returns_last_error_when_attempts_run_out() {
+    let calls = Cell::new(0);
+
+    let result: Result<(), String> = retry(3, Duration::from_millis(1), || {
+        calls.set(calls.get() + 1);
+        let attempt = calls.get();
+        async move { Err(format!("fail {}", attempt)) }
+    })
+    .await;
+
+    assert_eq!(result, Err("fail 3".to_string()));
+    assert_eq!(calls.get(), 3);
+}
+
+#[tokio::test(start_paused = true)]
+async fn backoff_is_capped_instead_of_overflowing() {
+    let started = Instant::now();
+
+    let result: Result<(), &str> = retry(4, Duration::MAX, || async { Err("down") }).await;
+
+    assert_eq!(result, Err("down"));
+    assert_eq!(started.elapsed(), Duration::from_secs(90));
+}
@@ -0,0 +1,276 @@
+#![cfg(feature = "testing")]
+
+use bolt_web::{
+    App,
+    async_trait::async_trait,
+    http::{HttpError, StatusCode},
+    request::RequestBody,
+    response::ResponseWriter,
+    testing::TestClient,
+    types::{Middleware, MiddlewareOutcome},
+};
+
+struct Deny;
+
+#[async_trait]
+impl Middleware for Deny {
+    async fn handle(&self, _req: &mut RequestBody, _res: &mut ResponseWriter) -> MiddlewareOutcome {
+        MiddlewareOutcome::Error(HttpError::new(StatusCode::Forbidden, "Forbidden"))
+    }
+}
+
+async fn ok(_: &mut RequestBody, res: &mut ResponseWriter) {
+    res.text("ok");
+}
+
+fn scoped_app() -> App {
+    let mut app = App::new();
+    app.set_banner(false);
+    app.middleware("/api", None, Deny);
+    app.get("/api/users", ok);
+    app.get("/apiary", ok);
+    app
+}
+
+async fn name(req: &mut RequestBody, res: &mut ResponseWriter) {
+    res.text(&req.param("name"));
+}
+
+fn strict_app() -> App {
+    let mut app = App::new();
+    app.set_banner(false);
+    app.set_strict_slash(true);
+    app.get("/:name", name);
+    app
+}
+
+#[tokio::test]
+async fn strict_slash_redirects_to_canonical_path() {
+    let client = TestClient::start(strict_app()).await.unwrap();
+
+    let res = client.get("/alice/?x=1").await.unwrap();
+    assert_eq!(res.status, 308);
+    assert_eq!(res.headers["location"], "/alice?x=1");
+
+    client.shutdown().await;
+}
+
+#[tokio::test]
+async fn strict_slash_never_redirects_off_site() {
+    let client = TestClient::start(strict_app()).await.unwrap();
+
+    let res = client.get("//evil.com/").await.unwrap();
+    assert_eq!(res.status, 308);
+    assert_eq!(res.headers["location"], "/evil.com");
+
+    let res = client.get("///evil.com/").await.unwrap();
+    assert_eq!(res.headers["location"], "/evil.com");
+
+    client.shutdown().await;
+}
+
+#[tokio::test]
+async fn non_strict_slash_serves_both_forms_without_redirect() {
+    let mut app = App::new();
+    app.set_banner(false);
+    app.get("/a", route);
+    app.get("/b/", route);
+    app.get("/users/:name", name);
+
+    let client = TestClient::start(app).await.unwrap();
+
+    for (path, body) in [
+        ("/a", "/a"),
+        ("/a/", "/a"),
+        ("/b", "/b/"),
+        ("/b/", "/b/"),
+        ("/users/alice", "alice"),
+        ("/users/alice/", "alice"),
+    ] {
+        let res = client.get(path).await.unwrap();
+        assert_eq!(res.status, 200, "{}", path);
+        assert!(res.headers.get("location").is_none(), "{}", path);
+        assert_eq!(res.body, body, "{}", path);
+    }
+
+    client.shutdown().await;
+}
+
+#[tokio::test]
+async fn static_scopes_match_whole_segments() {
+    let client = TestClient::start(scoped_app()).await.unwrap();
+
+    assert_eq!(client.get("/api/users").await.unwrap().status, 403);
+    assert_eq!(client.get("/apiary").await.unwrap().status, 200);
+
+    client.shutdown().await;
+}
+
+#[tokio::test]
+async fn duplicate_slashes_cannot_skip_middleware() {
+    let client = TestClient::start(scoped_app()).await.unwrap();
+
+    assert_eq!(client.get("//api/users").await.unwrap().status, 403);
+    assert_eq!(client.get("/api//users").await.unwrap().status, 403);
+
+    client.shutdown().await;
+}
+
+async fn generic(_: &mut RequestBody, res: &mut ResponseWriter) {
+    res.text("generic");
+}
+
+async fn regional(_: &mut RequestBody, res: &mut ResponseWriter) {
+    res.text("regional");
+}
+
+#[tokio::test]
+async fn longest_wildcard_host_wins() {
+    for _ in 0..4 {
+        let mut app = App::new();
+        app.set_banner(false);
+        app.host("*.example.com").get("/", generic);
+        app.host("*.eu.example.com").get("/", regional);
+        app.host("*.shop.eu.example.com").get("/", generic);
+
+        let client = TestClient::start(app).await.unwrap();
+
+        for (host, expected) in [
+            ("a.eu.example.com", "regional"),
+            ("a.b.eu.example.com:8080", "regional"),
+            ("a.example.com", "generic"),
+            ("a.shop.eu.example.com", "generic"),
+        ] {
+            let res = client
+                .request(hyper::Method::GET, "/", &[("host", host)], "")
+                .await
+                .unwrap();
+            assert_eq!(res.body, expected, "{}", host);
+        }
+
+        client.shutdown().await;
+    }
+}
+
+async fn route(req: &mut RequestBody, res: &mut ResponseWriter) {
+    res.text(req.route().unwrap_or_default());
+}
+
+#[tokio::test]
+async fn most_specific_dynamic_route_wins_across_prefixes() {
+    let mut app = App::new();
+    app.set_banner(false);
+    app.get("/files/*path", route);
+    app.get("/files/:name", route);
+    app.get("/:kind/a/b", route);
+    app.get("/users/:id", route);
+    app.get("/users/:id/posts", route);
+
+    let client = TestClient::start(app).await.unwrap();
+
+    for (path, expected) in [
+        ("/files/a/b", "/:kind/a/b"),
+        ("/files/a/c", "/files/*path"),
+        ("/files/a", "/files/:name"),
+        ("/users/42", "/users/:id"),
+        ("/users/42/posts", "/users/:id/posts"),
+        ("/docs/a/b", "/:kind/a/b"),
+    ] {
+        let res = client.get(path).await.unwrap();
+        assert_eq!(res.body, expected, "{}", path);
+    }
+
+    client.shutdown().await;
+}
+
+#[tokio::test]
+async fn equally_specific_routes_match_in_registration_order() {
+    for routes in [["/a/:x/b/:y", "/a/:x/:y/c"], ["/a/:x/:y/c", "/a/:x/b/:y"]] {
+        let mut app = App::new();
+        app.set_banner(false);
+        for path in routes {
+            app.get(path, route);
+        }
+
+        let client = TestClient::start(app).await.unwrap();
+
+        let res = client.get("/a/1/b/c").await.unwrap();
+        assert_eq!(res.body, routes[0]);
+
+        client.shutdown().await;
+    }
+}
+
+async fn file_path(req: &mut RequestBody, res: &mut ResponseWriter) {
+    res.text(&req.param("path"));
+}
+
+#[tokio::test]
+async fn wildcard_keeps_encoded_slashes_and_params_are_decoded() {
+    let mut app = App::new();
+    app.set_banner(false);
+    app.get("/files/*path", file_path);
+    app.get("/users/:name", name);
+
+    let client = TestClient::start(app).await.unwrap();
+
+    let res = client.get("/files/a/b%2Fc.txt").await.unwrap();
+    assert_eq!(res.status, 200);
+    assert_eq!(res.body, "a/b%2Fc.txt");
+
+    let res = client.get("/users/J%C3%B6rg").await.unwrap();
+    assert_eq!(res.body, "Jörg");
+
+    client.shutdown().await;
+}
+
+async fn params(req: &mut RequestBody, res: &mut ResponseWriter) {
+    let mut params: Vec<_> = req
+        .params()
+        .iter()
+        .map(|(k, v)| format!("{}={}", k, v))
+        .collect();
+    params.sort();
+    res.text(&format!(
+        "{} {}",
+        req.route().unwrap_or_default(),
+        params.join("&")
+    ));
+}
+
+#[tokio::test]
+async fn path_matching_edge_cases() {
+    let mut app = App::new();
+    app.set_banner(false);
+    app.get("/users/:id/edit", params);
+    app.get("/files/*path", params);
+    app.get("/teams/:team/:member", params);
+
+    let client = TestClient::start(app).await.unwrap();
+
+    for (path, status, body) in [
+        // A param sits between two static segments and needs both to match.
+        ("/users/7/edit", 200, "/users/:id/edit id=7"),
+        ("/users/7/view", 404, ""),
+        ("/users/7", 404, ""),
+        // Empty segments never satisfy a param.
+        ("/users//edit", 404, ""),
+        ("/teams/a//", 404, ""),
+        // A trailing slash is ignored outside strict mode.
+        ("/users/7/edit/", 200, "/users/:id/edit id=7"),
+        ("/teams/a/b/", 200, "/teams/:team/:member member=b&team=a"),
+        // A trailing wildcard takes everything after its prefix, even nothing, but
+        // not the trailing slash.
+        ("/files/a/b/c.txt", 200, "/files/*path path=a/b/c.txt"),
+        ("/files/a/", 200, "/files/*path path=a"),
+        ("/files", 200, "/files/*path path="),
+    ] {
+        let res = client.get(path).await.unwrap();
+        assert_eq!(res.status, status, "{}", path);
+        if status == 200 {
+            assert_eq!(res.body, body, "{}", path);
+        }
+    }
+
+    client.shutdown().await;
+}
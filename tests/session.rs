@@ -0,0 +1,172 @@
+#![cfg(feature = "testing")]
+
+use std::time::Duration;
+
+use bolt_web::{
+    App,
+    client::ClientResponse,
+    middleware::{MemoryStore, Session, SessionData},
+    request::RequestBody,
+    response::ResponseWriter,
+    testing::TestClient,
+};
+
+const SECRET: &[u8] = b"an-example-session-secret-of-32+bytes";
+
+async fn login(req: &mut RequestBody, res: &mut ResponseWriter) {
+    let session = req.ext::<SessionData>().unwrap();
+    session.regenerate();
+    session.insert("user", "alice");
+    res.text("welcome");
+}
+
+async fn count(req: &mut RequestBody, res: &mut ResponseWriter) {
+    let session = req.ext::<SessionData>().unwrap();
+    let visits = session.get::<u32>("visits").unwrap_or(0) + 1;
+    session.insert("visits", visits);
+    res.text(&visits.to_string());
+}
+
+async fn whoami(req: &mut RequestBody, res: &mut ResponseWriter) {
+    let session = req.ext::<SessionData>().unwrap();
+    res.text(&session.get::<String>("user").unwrap_or_default());
+}
+
+async fn logout(req: &mut RequestBody, res: &mut ResponseWriter) {
+    req.ext::<SessionData>().unwrap().destroy();
+    res.text("bye");
+}
+
+fn app(ttl: Duration) -> App {
+    let session = Session::new(MemoryStore::new(), SECRET)
+        .unwrap()
+        .secure(false)
+        .ttl(ttl);
+
+    let mut app = App::new();
+    app.set_banner(false);
+    app.middleware("/", None, session);
+    app.get("/login", login);
+    app.get("/count", count);
+    app.get("/whoami", whoami);
+    app.get("/logout", logout);
+    app
+}
+
+fn session_cookie(res: &ClientResponse) -> Option<String> {
+    res.headers
+        .get_all("set-cookie")
+        .iter()
+        .filter_map(|v| v.to_str().ok())
+        .find(|v| v.starts_with("bolt.sid="))
+        .and_then(|v| v.split(';').next())
+        .map(str::to_string)
+}
+
+async fn get_with(client: &TestClient, path: &str, cookie: &str) -> ClientResponse {
+    client
+        .request(hyper::Method::GET, path, &[("cookie", cookie)], "")
+        .await
+        .unwrap()
+}
+
+#[test]
+fn short_secrets_are_rejected() {
+    assert!(Session::new(MemoryStore::new(), b"too-short").is_err());
+}
+
+#[tokio::test]
+async fn session_persists_across_requests() {
+    let client = TestClient::start(app(Duration::from_secs(60)))
+        .await
+        .unwrap();
+
+    let res = client.get("/count").await.unwrap();
+    assert_eq!(res.body, "1");
+    let cookie = session_cookie(&res).expect("session cookie");
+
+    let res = get_with(&client, "/count", &cookie).await;
+    assert_eq!(res.body, "2");
+    let res = get_with(&client, "/count", &cookie).await;
+    assert_eq!(res.body, "3");
+
+    let res = client.get("/count").await.unwrap();
+    assert_eq!(res.body, "1");
+
+    client.shutdown().await;
+}
+
+#[tokio::test]
+async fn unchanged_sessions_set_no_cookie() {
+    let client = TestClient::start(app(Duration::from_secs(60)))
+        .await
+        .unwrap();
+
+    let res = client.get("/whoami").await.unwrap();
+    assert_eq!(session_cookie(&res), None);
+
+    client.shutdown().await;
+}
+
+#[tokio::test]
+async fn expired_sessions_start_over() {
+    let client = TestClient::start(app(Duration::from_millis(200)))
+        .await
+        .unwrap();
+
+    let res = client.get("/count").await.unwrap();
+    let cookie = session_cookie(&res).unwrap();
+    assert_eq!(get_with(&client, "/count", &cookie).await.body, "2");
+
+    tokio::time::sleep(Duration::from_millis(400)).await;
+    assert_eq!(get_with(&client, "/count", &cookie).await.body, "1");
+
+    client.shutdown().await;
+}
+
+#[tokio::test]
+async fn regenerate_moves_data_to_a_new_id() {
+    let client = TestClient::start(app(Duration::from_secs(60)))
+        .await
+        .unwrap();
+
+    let res = client.get("/count").await.unwrap();
+    let before = session_cookie(&res).unwrap();
+
+    let res = get_with(&client, "/login", &before).await;
+    let after = session_cookie(&res).unwrap();
+    assert_ne!(before, after);
+
+    assert_eq!(get_with(&client, "/whoami", &after).await.body, "alice");
+    assert_eq!(get_with(&client, "/count", &after).await.body, "2");
+    assert_eq!(get_with(&client, "/whoami", &before).await.body, "");
+
+    client.shutdown().await;
+}
+
+#[tokio::test]
+async fn destroy_removes_the_session_and_expires_the_cookie() {
+    let client = TestClient::start(app(Duration::from_secs(60)))
+        .await
+        .unwrap();
+
+    let res = client.get("/login").await.unwrap();
+    let cookie = session_cookie(&res).unwrap();
+    assert_eq!(get_with(&client, "/whoami", &cookie).await.body, "alice");
+
+    let res = get_with(&client, "/logout", &cookie).await;
+    let cleared = res
+        .headers
+        .get_all("set-cookie")
+        .iter()
+        .filter_map(|v| v.to_str().ok())
+        .find(|v| v.starts_with("bolt.sid="))
+        .unwrap()
+        .to_string();
+    assert!(cleared.starts_with("bolt.sid=;"), "{}", cleared);
+    assert!(cleared.contains("Max-Age=0"), "{}", cleared);
+
+    assert_eq!(get_with(&client, "/whoami", &cookie).await.body, "");
+
+    client.shutdown().await;
+}
@@ -0,0 +1,157 @@
+#![cfg(feature = "testing")]
+
+use bolt_web::{
+    App,
+    async_trait::async_trait,
+    http::StatusCode,
+    request::RequestBody,
+    response::ResponseWriter,
+    testing::TestClient,
+    types::{Middleware, MiddlewareOutcome},
+};
+
+struct Preflight;
+
+#[async_trait]
+impl Middleware for Preflight {
+    async fn handle(&self, _req: &mut RequestBody, res: &mut ResponseWriter) -> MiddlewareOutcome {
+        res.status_code(204);
+        MiddlewareOutcome::Stop
+    }
+}
+
+async fn coded(req: &mut RequestBody, res: &mut ResponseWriter) {
+    let code = req.param("code").parse().unwrap_or(0);
+    res.status_code(code).text("coded");
+}
+
+fn app() -> App {
+    let mut app = App::new();
+    app.set_banner(false);
+    app.get("/code/:code", coded);
+    app.middleware("/preflight", None, Preflight);
+    app.get("/preflight", coded);
+    app
+}
+
+#[tokio::test]
+async fn known_codes_are_emitted() {
+    let client = TestClient::start(app()).await.unwrap();
+
+    let res = client.get("/code/200").await.unwrap();
+    assert_eq!(res.status, 200);
+    assert_eq!(res.body, "coded");
+
+    let res = client.get("/code/418").await.unwrap();
+    assert_eq!(res.status, 418);
+
+    client.shutdown().await;
+}
+
+#[tokio::test]
+async fn unusual_valid_codes_are_kept_raw() {
+    let client = TestClient::start(app()).await.unwrap();
+
+    let res = client.get("/code/299").await.unwrap();
+    assert_eq!(res.status, 299);
+    assert_eq!(res.body, "coded");
+
+    client.shutdown().await;
+}
+
+#[tokio::test]
+async fn out_of_range_codes_fall_back_to_500() {
+    let client = TestClient::start(app()).await.unwrap();
+
+    let res = client.get("/code/1000").await.unwrap();
+    assert_eq!(res.status, 500);
+
+    client.shutdown().await;
+}
+
+#[tokio::test]
+async fn middleware_can_set_numeric_status() {
+    let client = TestClient::start(app()).await.unwrap();
+
+    let res = client.get("/preflight").await.unwrap();
+    assert_eq!(res.status, 204);
+    assert!(res.body.is_empty());
+
+    client.shutdown().await;
+}
+
+const NAMED: &[StatusCode] = &[
+    StatusCode::Continue,
+    StatusCode::SwitchingProtocols,
+    StatusCode::Processing,
+    StatusCode::EarlyHints,
+    StatusCode::OK,
+    StatusCode::Created,
+    StatusCode::Accepted,
+    StatusCode::NonAuthoritativeInformation,
+    StatusCode::NoContent,
+    StatusCode::ResetContent,
+    StatusCode::PartialContent,
+    StatusCode::MovedPermanently,
+    StatusCode::Found,
+    StatusCode::SeeOther,
+    StatusCode::NotModified,
+    StatusCode::TemporaryRedirect,
+    StatusCode::PermanentRedirect,
+    StatusCode::BadRequest,
+    StatusCode::Unauthorized,
+    StatusCode::PaymentRequired,
+    StatusCode::Forbidden,
+    StatusCode::NotFound,
+    StatusCode::MethodNotAllowed,
+    StatusCode::NotAcceptable,
+    StatusCode::ProxyAuthenticationRequired,
+    StatusCode::RequestTimeout,
+    StatusCode::Conflict,
+    StatusCode::Gone,
+    StatusCode::LengthRequired,
+    StatusCode::PreconditionFailed,
+    StatusCode::ContentTooLarge,
+    StatusCode::URITooLong,
+    StatusCode::UnsupportedMediaType,
+    StatusCode::RangeNotSatisfiable,
+    StatusCode::ImATeapot,
+    StatusCode::UnprocessableEntity,
+    StatusCode::PreconditionRequired,
+    StatusCode::TooManyRequests,
+    StatusCode::RequestHeaderFieldsTooLarge,
+    StatusCode::UnavailableForLegalReasons,
+    StatusCode::InternalServerError,
+    StatusCode::NotImplemented,
+    StatusCode::BadGateway,
+    StatusCode::ServiceUnavailable,
+    StatusCode::GatewayTimeout,
+    StatusCode::HTTPVersionNotSupported,
+    StatusCode::InsufficientStorage,
+    StatusCode::NetworkAuthenticationRequired,
+];
+
+#[test]
+fn every_named_code_round_trips() {
+    for &code in NAMED {
+        assert_eq!(
+            StatusCode::from_u16(code.as_u16()),
+            Some(code),
+            "{:?}",
+            code
+        );
+    }
+
+    for n in 100..=999 {
+        if let Some(code) = StatusCode::from_u16(n) {
+            assert_eq!(code.as_u16(), n);
+            assert!(NAMED.contains(&code), "{:?} missing from NAMED", code);
+        }
+    }
+}
+
+#[test]
+fn unknown_codes_have_no_named_variant() {
+    assert_eq!(StatusCode::from_u16(299), None);
+    assert_eq!(StatusCode::from_u16(0), None);
+}
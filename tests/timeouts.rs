@@ -0,0 +1,272 @@
+#![cfg(feature = "testing")]
+
+use std::time::{Duration, Instant};
+
+use bolt_web::{
+    App,
+    client::{Client, ClientConfig},
+    request::RequestBody,
+    response::ResponseWriter,
+    testing::TestClient,
+    types::{BoltError, Timeouts},
+};
+use tokio::{
+    io::{AsyncReadExt, AsyncWriteExt},
+    net::{TcpListener, TcpStream},
+};
+
+const SHORT: Duration = Duration::from_millis(200);
+const LONG: Duration = Duration::from_secs(30);
+
+async fn ok(_: &mut RequestBody, res: &mut ResponseWriter) {
+    res.text("ok");
+}
+
+async fn slow(_: &mut RequestBody, res: &mut ResponseWriter) {
+    tokio::time::sleep(Duration::from_secs(5)).await;
+    res.text("late");
+}
+
+async fn upload(req: &mut RequestBody, res: &mut ResponseWriter) -> Result<(), BoltError> {
+    let body = req.bytes().await?;
+    res.text(&body.len().to_string());
+    Ok(())
+}
+
+async fn proxy(req: &mut RequestBody, res: &mut ResponseWriter) -> Result<(), BoltError> {
+    let upstream = req.query_param("upstream").unwrap_or_default();
+    let client = Client::with_config(ClientConfig {
+        timeout: Some(SHORT),
+        ..ClientConfig::default()
+    });
+
+    let body = client.get_raw(&upstream, &None).await?;
+    res.bytes(&body);
+    Ok(())
+}
+
+fn app(timeouts: Timeouts) -> App {
+    let mut app = App::new();
+    app.set_banner(false);
+    app.set_timeouts(timeouts);
+    app.get("/", ok);
+    app.get("/slow", slow);
+    app.post("/upload", upload);
+    app.get("/proxy", proxy);
+    app
+}
+
+fn relaxed() -> Timeouts {
+    Timeouts {
+        header_read: LONG,
+        body_read: LONG,
+        handler: LONG,
+        idle_keepalive: LONG,
+        total: None,
+    }
+}
+
+async fn read_response(stream: &mut TcpStream) -> String {
+    let mut buf = Vec::new();
+    let mut chunk = [0; 1024];
+
+    loop {
+        let n = stream.read(&mut chunk).await.unwrap_or(0);
+        if n == 0 {
+            break;
+        }
+        buf.extend_from_slice(&chunk[..n]);
+
+        let text = String::from_utf8_lossy(&buf);
+        if let Some((head, body)) = text.split_once("\r\n\r\n") {
+            let len = head
+                .lines()
+                .find_map(|l| {
+                    l.to_ascii_lowercase()
+                        .strip_prefix("content-length: ")
+                        .map(str::to_string)
+                })
+                .and_then(|v| v.trim().parse::<usize>().ok())
+                .unwrap_or(0);
+            if body.len() >= len {
+                break;
+            }
+        }
+    }
+
+    String::from_utf8_lossy(&buf).into_owned()
+}
+
+async fn closes_within(stream: &mut TcpStream, limit: Duration) -> bool {
+    let mut chunk = [0; 1024];
+    let deadline = Instant::now() + limit;
+
+    loop {
+        let remaining = deadline.saturating_duration_since(Instant::now());
+        match tokio::time::timeout(remaining, stream.read(&mut chunk)).await {
+            Ok(Ok(0)) | Ok(Err(_)) => return true,
+            Ok(Ok(_)) => continue,
+            Err(_) => return false,
+        }
+    }
+}
+
+const GET: &[u8] = b"GET / HTTP/1.1\r\nHost: test\r\n\r\n";
+
+#[tokio::test]
+async fn header_read_closes_connections_with_incomplete_headers() {
+    let client = TestClient::start(app(Timeouts {
+        header_read: SHORT,
+        ..relaxed()
+    }))
+    .await
+    .unwrap();
+
+    let mut stream = TcpStream::connect(client.addr()).await.unwrap();
+    stream
+        .write_all(b"GET / HTTP/1.1\r\nHost: te")
+        .await
+        .unwrap();
+    assert!(closes_within(&mut stream, Duration::from_secs(2)).await);
+
+    client.shutdown().await;
+}
+
+#[tokio::test]
+async fn header_read_ignores_the_idle_gap_between_keepalive_requests() {
+    let client = TestClient::start(app(Timeouts {
+        header_read: SHORT,
+        ..relaxed()
+    }))
+    .await
+    .unwrap();
+
+    let mut stream = TcpStream::connect(client.addr()).await.unwrap();
+    stream.write_all(GET).await.unwrap();
+    let response = read_response(&mut stream).await;
+    assert!(response.starts_with("HTTP/1.1 200"), "{}", response);
+
+    tokio::time::sleep(SHORT * 3).await;
+
+    stream.write_all(GET).await.unwrap();
+    let response = read_response(&mut stream).await;
+    assert!(response.starts_with("HTTP/1.1 200"), "{}", response);
+
+    client.shutdown().await;
+}
+
+#[tokio::test]
+async fn body_read_times_out_slow_uploads() {
+    let client = TestClient::start(app(Timeouts {
+        body_read: SHORT,
+        ..relaxed()
+    }))
+    .await
+    .unwrap();
+
+    let mut stream = TcpStream::connect(client.addr()).await.unwrap();
+    stream
+        .write_all(b"POST /upload HTTP/1.1\r\nHost: test\r\nContent-Length: 100\r\n\r\nonly ten b")
+        .await
+        .unwrap();
+
+    let started = Instant::now();
+    let response = read_response(&mut stream).await;
+    assert!(response.starts_with("HTTP/1.1 408"), "{}", response);
+    assert!(started.elapsed() < Duration::from_secs(2));
+
+    client.shutdown().await;
+}
+
+#[tokio::test]
+async fn handler_timeout_answers_504() {
+    let client = TestClient::start(app(Timeouts {
+        handler: SHORT,
+        ..relaxed()
+    }))
+    .await
+    .unwrap();
+
+    let started = Instant::now();
+    let res = client.get("/slow").await.unwrap();
+    assert_eq!(res.status, 504);
+    assert!(started.elapsed() < Duration::from_secs(2));
+
+    client.shutdown().await;
+}
+
+#[tokio::test]
+async fn silent_upstream_answers_504() {
+    let upstream = TcpListener::bind("127.0.0.1:0").await.unwrap();
+    let upstream_addr = upstream.local_addr().unwrap();
+    // Accept connections and hold them open without ever replying.
+    let silent = tokio::spawn(async move {
+        let mut held = Vec::new();
+        while let Ok((stream, _)) = upstream.accept().await {
+            held.push(stream);
+        }
+    });
+
+    let client = TestClient::start(app(relaxed())).await.unwrap();
+
+    let started = Instant::now();
+    let res = client
+        .get(&format!("/proxy?upstream=http://{}/", upstream_addr))
+        .await
+        .unwrap();
+    assert_eq!(res.status, 504);
+    assert!(started.elapsed() < Duration::from_secs(2));
+
+    client.shutdown().await;
+    silent.abort();
+}
+
+#[tokio::test]
+async fn idle_keepalive_closes_quiet_connections() {
+    let client = TestClient::start(app(Timeouts {
+        idle_keepalive: SHORT,
+        ..relaxed()
+    }))
+    .await
+    .unwrap();
+
+    let mut stream = TcpStream::connect(client.addr()).await.unwrap();
+    stream.write_all(GET).await.unwrap();
+    let response = read_response(&mut stream).await;
+    assert!(response.starts_with("HTTP/1.1 200"), "{}", response);
+
+    assert!(closes_within(&mut stream, Duration::from_secs(2)).await);
+
+    client.shutdown().await;
+}
+
+#[tokio::test]
+async fn total_caps_busy_connections() {
+    let client = TestClient::start(app(Timeouts {
+        total: Some(Duration::from_millis(500)),
+        ..relaxed()
+    }))
+    .await
+    .unwrap();
+
+    let mut stream = TcpStream::connect(client.addr()).await.unwrap();
+    let started = Instant::now();
+    let mut served = 0;
+
+    while started.elapsed() < Duration::from_secs(3) {
+        if stream.write_all(GET).await.is_err() {
+            break;
+        }
+        let response = read_response(&mut stream).await;
+        if response.is_empty() {
+            break;
+        }
+        served += 1;
+        tokio::time::sleep(Duration::from_millis(50)).await;
+    }
+
+    assert!(served > 1, "connection was closed before it got busy");
+    assert!(started.elapsed() < Duration::from_secs(2));
+
+    client.shutdown().await;
+}
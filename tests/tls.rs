@@ -0,0 +1,161 @@
+#![cfg(feature = "testing")]
+
+use std::{sync::Arc, time::Duration};
+
+use bolt_web::{
+    App, TlsOptions, TlsVersion, request::RequestBody, response::ResponseWriter, types::Mode,
+};
+use rustls::{
+    ClientConfig, SupportedProtocolVersion,
+    pki_types::{CertificateDer, ServerName, pem::PemObject},
+    version::TLS12,
+};
+use tokio::{
+    io::{AsyncReadExt, AsyncWriteExt},
+    net::{TcpListener, TcpStream},
+    sync::oneshot,
+};
+use tokio_rustls::TlsConnector;
+
+const CERT: &str = concat!(env!("CARGO_MANIFEST_DIR"), "/tests/fixtures/cert.pem");
+const KEY: &str = concat!(env!("CARGO_MANIFEST_DIR"), "/tests/fixtures/key.pem");
+const CERT2: &str = concat!(env!("CARGO_MANIFEST_DIR"), "/tests/fixtures/cert2.pem");
+const KEY2: &str = concat!(env!("CARGO_MANIFEST_DIR"), "/tests/fixtures/key2.pem");
+
+async fn sni(req: &mut RequestBody, res: &mut ResponseWriter) {
+    res.text(req.sni().unwrap_or("none"));
+}
+
+fn app() -> App {
+    let mut app = App::new();
+    app.set_banner(false);
+    app.get("/sni", sni);
+    app
+}
+
+async fn start(app: App) -> (String, oneshot::Sender<()>) {
+    let addr = TcpListener::bind("127.0.0.1:0")
+        .await
+        .unwrap()
+        .local_addr()
+        .unwrap()
+        .to_string();
+
+    let (tx, rx) = oneshot::channel::<()>();
+    let bind = addr.clone();
+    tokio::spawn(async move {
+        let shutdown = async move {
+            let _ = rx.await;
+        };
+        app.run_tls_with_shutdown(&bind, Mode::Http1, Some((CERT, KEY)), shutdown)
+            .await
+            .unwrap();
+    });
+
+    for _ in 0..50 {
+        if TcpStream::connect(&addr).await.is_ok() {
+            break;
+        }
+        tokio::time::sleep(Duration::from_millis(10)).await;
+    }
+
+    (addr, tx)
+}
+
+fn client_config(versions: Option<&[&'static SupportedProtocolVersion]>) -> ClientConfig {
+    let mut roots = rustls::RootCertStore::empty();
+    for cert in [CERT, CERT2] {
+        roots
+            .add(CertificateDer::from_pem_file(cert).unwrap())
+            .unwrap();
+    }
+
+    let builder = match versions {
+        Some(versions) => ClientConfig::builder_with_protocol_versions(versions),
+        None => ClientConfig::builder(),
+    };
+    builder.with_root_certificates(roots).with_no_client_auth()
+}
+
+/// Handshakes with a fresh config, so no session is resumed, and returns the server's leaf.
+async fn handshake(
+    addr: &str,
+    versions: Option<&[&'static SupportedProtocolVersion]>,
+) -> std::io::Result<CertificateDer<'static>> {
+    let connector = TlsConnector::from(Arc::new(client_config(versions)));
+    let tcp = TcpStream::connect(addr).await?;
+    let tls = connector
+        .connect(ServerName::try_from("localhost").unwrap(), tcp)
+        .await?;
+
+    Ok(tls.get_ref().1.peer_certificates().unwrap()[0].clone())
+}
+
+async fn fetch_sni(addr: &str, server_name: &str) -> String {
+    let connector = TlsConnector::from(Arc::new(client_config(None)));
+
+    let tcp = TcpStream::connect(addr).await.unwrap();
+    let name = ServerName::try_from(server_name.to_string()).unwrap();
+    let mut tls = connector.connect(name, tcp).await.unwrap();
+
+    tls.write_all(b"GET /sni HTTP/1.1\r\nHost: localhost\r\nConnection: close\r\n\r\n")
+        .await
+        .unwrap();
+
+    let mut response = String::new();
+    tls.read_to_string(&mut response).await.unwrap();
+    response
+        .rsplit("\r\n\r\n")
+        .next()
+        .unwrap_or_default()
+        .to_string()
+}
+
+#[tokio::test]
+async fn sni_sent_by_the_client_is_captured() {
+    let (addr, shutdown) = start(app()).await;
+
+    assert_eq!(fetch_sni(&addr, "localhost").await, "localhost");
+    // rustls never sends SNI for an IP address.
+    assert_eq!(fetch_sni(&addr, "127.0.0.1").await, "none");
+
+    let _ = shutdown.send(());
+}
+
+#[tokio::test]
+async fn tls12_only_clients_depend_on_the_enabled_versions() {
+    for (versions, accepted) in [
+        (vec![TlsVersion::Tls12, TlsVersion::Tls13], true),
+        (vec![TlsVersion::Tls13], false),
+    ] {
+        let mut app = app();
+        app.set_tls_options(TlsOptions {
+            versions,
+            ..Default::default()
+        })
+        .unwrap();
+        let (addr, shutdown) = start(app).await;
+
+        let result = handshake(&addr, Some(&[&TLS12])).await;
+        assert_eq!(result.is_ok(), accepted, "{:?}", result.err());
+
+        let _ = shutdown.send(());
+    }
+}
+
+#[tokio::test]
+async fn reloaded_certificate_is_used_for_new_handshakes() {
+    let app = app();
+    let tls = app.tls_handle();
+    let (addr, shutdown) = start(app).await;
+
+    let first = CertificateDer::from_pem_file(CERT).unwrap();
+    let second = CertificateDer::from_pem_file(CERT2).unwrap();
+
+    assert_eq!(handshake(&addr, None).await.unwrap(), first);
+
+    tls.reload(CERT2, KEY2).unwrap();
+    assert_eq!(handshake(&addr, None).await.unwrap(), second);
+
+    let _ = shutdown.send(());
+}
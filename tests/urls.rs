@@ -0,0 +1,114 @@
+#![cfg(feature = "testing")]
+
+use bolt_web::{App, request::RequestBody, response::ResponseWriter, testing::TestClient};
+
+async fn base(req: &mut RequestBody, res: &mut ResponseWriter) {
+    res.text(&req.base_url().unwrap_or_default());
+}
+
+async fn login(_: &mut RequestBody, res: &mut ResponseWriter) {
+    res.redirect_temporary("/login?next=%2Fdocs");
+}
+
+async fn sibling(_: &mut RequestBody, res: &mut ResponseWriter) {
+    res.redirect_temporary("intro");
+}
+
+fn app(trusted: bool) -> App {
+    let mut app = App::new();
+    app.set_banner(false);
+    if trusted {
+        app.trust_proxy("127.0.0.1".parse().unwrap());
+    }
+    app.get("/base", base);
+    app.get("/docs/start", sibling);
+    app.get("/private", login);
+    app
+}
+
+const FORWARDED: [(&str, &str); 3] = [
+    ("host", "10.0.0.5:8080"),
+    ("x-forwarded-host", "example.com"),
+    ("x-forwarded-proto", "https"),
+];
+
+#[tokio::test]
+async fn base_url_uses_forwarded_headers_from_trusted_proxies() {
+    let client = TestClient::start(app(true)).await.unwrap();
+
+    let res = client
+        .request(hyper::Method::GET, "/base", &FORWARDED, "")
+        .await
+        .unwrap();
+    assert_eq!(res.body, "https://example.com");
+
+    client.shutdown().await;
+}
+
+#[tokio::test]
+async fn base_url_ignores_forwarded_headers_from_untrusted_peers() {
+    let client = TestClient::start(app(false)).await.unwrap();
+
+    let res = client
+        .request(hyper::Method::GET, "/base", &FORWARDED, "")
+        .await
+        .unwrap();
+    assert_eq!(res.body, "http://10.0.0.5:8080");
+
+    client.shutdown().await;
+}
+
+#[tokio::test]
+async fn relative_redirects_stay_relative_without_a_public_url() {
+    let client = TestClient::start(app(true)).await.unwrap();
+
+    let res = client
+        .request(hyper::Method::GET, "/private", &FORWARDED, "")
+        .await
+        .unwrap();
+    assert_eq!(res.status, 307);
+    assert_eq!(res.headers["location"], "/login?next=%2Fdocs");
+
+    let res = client
+        .request(
+            hyper::Method::GET,
+            "/docs/start",
+            &[("host", "evil.example")],
+            "",
+        )
+        .await
+        .unwrap();
+    assert_eq!(res.headers["location"], "intro");
+
+    client.shutdown().await;
+}
+
+#[tokio::test]
+async fn relative_redirects_resolve_against_the_public_url() {
+    let mut app = app(false);
+    app.set_public_url("https://example.com/app/").unwrap();
+    let client = TestClient::start(app).await.unwrap();
+
+    let res = client
+        .request(
+            hyper::Method::GET,
+            "/private",
+            &[("host", "evil.example")],
+            "",
+        )
+        .await
+        .unwrap();
+    assert_eq!(res.status, 307);
+    assert_eq!(
+        res.headers["location"],
+        "https://example.com/login?next=%2Fdocs"
+    );
+
+    let res = client.get("/docs/start").await.unwrap();
+    assert_eq!(
+        res.headers["location"],
+        "https://example.com/app/docs/intro"
+    );
+
+    client.shutdown().await;
+}